@@ -41,11 +41,35 @@ pub struct Cli {
     #[arg(long)]
     pub hidden: Option<bool>,
 
+    /// Whether to include generated and vendored files (true/false)
+    #[arg(long)]
+    pub generated: Option<bool>,
+
+    /// Mark detection mode (uppercase|case-insensitive|uppercase-or-colon)
+    #[arg(long, value_enum)]
+    pub detection: Option<DetectionArg>,
+
+    /// Where marks are recognized inside a comment (leading|anywhere)
+    #[arg(long, value_enum)]
+    pub mark_position: Option<MarkPositionArg>,
+
+    /// Whether to join following comment lines into a mark (true/false)
+    #[arg(long)]
+    pub multiline: Option<bool>,
+
+    /// Whether to report unchecked Markdown task items as TODO marks (true/false)
+    #[arg(long)]
+    pub task_items: Option<bool>,
+
+    /// Whether to report marks inside C/C++ `#if 0` blocks (true/false)
+    #[arg(long)]
+    pub disabled_code: Option<bool>,
+
     /// Read buffer size in bytes
     #[arg(long)]
     pub read_buffer_size: Option<usize>,
 
-    /// Sort pipeline stages (comma separated). Example: mark,language,folder
+    /// Sort pipeline stages (comma separated). Example: mark,language,folder,owner
     #[arg(long, value_name = "STAGES")]
     pub sort: Option<String>,
 
@@ -101,11 +125,66 @@ pub struct Cli {
     #[arg(long = "filter-folder-deny", value_name = "PATH")]
     pub filter_folder_deny: Vec<PathBuf>,
 
+    /// Allow list for mark owners (repeatable)
+    #[arg(long = "filter-owner", value_name = "OWNER")]
+    pub filter_owner: Vec<String>,
+
+    /// Deny list for mark owners (repeatable)
+    #[arg(long = "filter-owner-deny", value_name = "OWNER")]
+    pub filter_owner_deny: Vec<String>,
+
+    /// Allow list for issue references (repeatable)
+    #[arg(long = "filter-issue", value_name = "ISSUE")]
+    pub filter_issue: Vec<String>,
+
+    /// Deny list for issue references (repeatable)
+    #[arg(long = "filter-issue-deny", value_name = "ISSUE")]
+    pub filter_issue_deny: Vec<String>,
+
+    /// Allow list for comment kinds (repeatable)
+    #[arg(long = "filter-comment", value_enum, value_name = "KIND")]
+    pub filter_comment: Vec<CommentKindArg>,
+
+    /// Deny list for comment kinds (repeatable)
+    #[arg(long = "filter-comment-deny", value_enum, value_name = "KIND")]
+    pub filter_comment_deny: Vec<CommentKindArg>,
+
+    /// Only show marks whose due date has passed
+    #[arg(long)]
+    pub overdue: bool,
+
+    /// Only show marks due before a date (YYYY-MM-DD, or YYYY-MM / YYYY-Qn for the start of that period)
+    #[arg(long, value_name = "DATE")]
+    pub due_before: Option<String>,
+
     /// Disable file headers in output
     #[arg(long)]
     pub no_file_header: bool,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DetectionArg {
+    Uppercase,
+    CaseInsensitive,
+    UppercaseOrColon,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum MarkPositionArg {
+    Leading,
+    Anywhere,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CommentKindArg {
+    Line,
+    Block,
+    DocLine,
+    DocBlock,
+    Docstring,
+    Task,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum SortOrderArg {
     Asc,
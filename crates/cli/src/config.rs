@@ -6,12 +6,16 @@ use config::{Config as ConfigSource, ConfigError, Environment, File};
 use serde::Deserialize;
 
 use doto_core::{
-    DimensionStage, DimensionValue, FilterConfig, FilterRule, FolderSortConfig, LanguageOrder,
-    LanguageSortConfig, MarkPriorityOverride, MarkSortConfig, Order, PathSortConfig, SortConfig,
-    ValuePredicate,
+    CommentKind, CommentSortConfig, DetectionMode, DimensionStage, DimensionValue, DueDate,
+    DueSortConfig, FilterConfig, FilterRule, FolderSortConfig, IssueSortConfig, LanguageDefinition,
+    LanguageMap, LanguageMapping, LanguageOrder, LanguageSortConfig, MarkAlias, MarkDefinition,
+    MarkPattern, MarkPosition, MarkPriorityOverride, MarkRegistry, MarkSortConfig, Order,
+    OwnerSortConfig, PathSortConfig, SortConfig, ValuePredicate, canonical_language,
 };
 
-use crate::cli::{Cli, SortLangOrderArg, SortOrderArg};
+use crate::cli::{
+    Cli, CommentKindArg, DetectionArg, MarkPositionArg, SortLangOrderArg, SortOrderArg,
+};
 
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
@@ -21,16 +25,92 @@ pub struct Config {
     pub exclude: Vec<String>,
     pub gitignore: Option<bool>,
     pub hidden: Option<bool>,
+    pub generated: Option<bool>,
+    pub detection: Option<DetectionMode>,
+    pub mark_position: Option<MarkPosition>,
+    pub multiline: Option<bool>,
+    pub task_items: Option<bool>,
+    pub disabled_code: Option<bool>,
+    pub languages: Vec<LanguageDefinition>,
+    pub language_map: Vec<LanguageMapping>,
     pub read_buffer_size: Option<usize>,
     pub sort: Option<SortConfig>,
     pub filter: Option<FilterConfig>,
+    pub marks: MarksConfig,
     pub file_header: bool,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MarksConfig {
+    pub builtin: bool,
+    pub add: Vec<MarkDefinition>,
+    pub remove: Vec<String>,
+    pub aliases: Vec<MarkAlias>,
+    pub patterns: Vec<MarkPattern>,
+}
+
+impl Default for MarksConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            add: Vec::new(),
+            remove: Vec::new(),
+            aliases: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl MarksConfig {
+    pub fn registry(&self) -> Result<MarkRegistry, Box<dyn Error>> {
+        let mut registry = if self.builtin {
+            MarkRegistry::builtin()
+        } else {
+            MarkRegistry::empty()
+        };
+        for name in &self.remove {
+            registry.remove(name);
+        }
+        for definition in &self.add {
+            registry.insert(&definition.name, definition.priority);
+        }
+        for pattern in &self.patterns {
+            registry
+                .add_pattern(&pattern.name, &pattern.pattern, pattern.priority)
+                .map_err(|err| format!("invalid mark pattern '{}': {err}", pattern.name))?;
+        }
+        for alias in &self.aliases {
+            if !registry.add_alias(&alias.alias, &alias.mark) {
+                return Err(
+                    format!("invalid mark alias '{}' -> '{}'", alias.alias, alias.mark).into(),
+                );
+            }
+        }
+        Ok(registry)
+    }
+}
+
+fn language_value(value: &str) -> DimensionValue {
+    DimensionValue::Language(
+        canonical_language(value).map_or_else(|| value.to_string().into(), Into::into),
+    )
+}
+
+pub fn language_map(mappings: &[LanguageMapping]) -> Result<LanguageMap, Box<dyn Error>> {
+    let mut map = LanguageMap::new();
+    for mapping in mappings {
+        map.insert(&mapping.pattern, &mapping.language)
+            .map_err(|err| format!("invalid language mapping '{}': {err}", mapping.pattern))?;
+    }
+    Ok(map)
+}
+
 pub fn load_config(config_path: Option<&PathBuf>) -> Result<Config, ConfigError> {
     let mut builder = ConfigSource::builder()
         .set_default("gitignore", true)?
         .set_default("hidden", false)?
+        .set_default("generated", false)?
         .set_default("read_buffer_size", 64 * 1024)?
         .set_default("file_header", true)?;
 
@@ -76,6 +156,31 @@ pub fn apply_args(config: &mut Config, args: &Cli) {
     if let Some(hidden) = args.hidden {
         config.hidden = Some(hidden);
     }
+    if let Some(generated) = args.generated {
+        config.generated = Some(generated);
+    }
+    if let Some(detection) = args.detection {
+        config.detection = Some(match detection {
+            DetectionArg::Uppercase => DetectionMode::Uppercase,
+            DetectionArg::CaseInsensitive => DetectionMode::CaseInsensitive,
+            DetectionArg::UppercaseOrColon => DetectionMode::UppercaseOrColon,
+        });
+    }
+    if let Some(position) = args.mark_position {
+        config.mark_position = Some(match position {
+            MarkPositionArg::Leading => MarkPosition::Leading,
+            MarkPositionArg::Anywhere => MarkPosition::Anywhere,
+        });
+    }
+    if let Some(multiline) = args.multiline {
+        config.multiline = Some(multiline);
+    }
+    if let Some(task_items) = args.task_items {
+        config.task_items = Some(task_items);
+    }
+    if let Some(disabled_code) = args.disabled_code {
+        config.disabled_code = Some(disabled_code);
+    }
     if let Some(read_buffer_size) = args.read_buffer_size {
         config.read_buffer_size = Some(read_buffer_size);
     }
@@ -176,7 +281,7 @@ pub fn resolve_filter_config(
                 values: args
                     .filter_language
                     .iter()
-                    .map(|value| DimensionValue::Language(value.clone().into()))
+                    .map(|value| language_value(value))
                     .collect(),
             },
         });
@@ -189,7 +294,7 @@ pub fn resolve_filter_config(
                 values: args
                     .filter_language_deny
                     .iter()
-                    .map(|value| DimensionValue::Language(value.clone().into()))
+                    .map(|value| language_value(value))
                     .collect(),
             },
         });
@@ -247,6 +352,103 @@ pub fn resolve_filter_config(
         });
     }
 
+    if !args.filter_owner.is_empty() {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Owner(OwnerSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: args
+                    .filter_owner
+                    .iter()
+                    .map(|value| DimensionValue::Owner(value.clone().into()))
+                    .collect(),
+            },
+        });
+    }
+
+    if !args.filter_owner_deny.is_empty() {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Owner(OwnerSortConfig::default()),
+            predicate: ValuePredicate::Deny {
+                values: args
+                    .filter_owner_deny
+                    .iter()
+                    .map(|value| DimensionValue::Owner(value.clone().into()))
+                    .collect(),
+            },
+        });
+    }
+
+    if !args.filter_issue.is_empty() {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Issue(IssueSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: args
+                    .filter_issue
+                    .iter()
+                    .map(|value| DimensionValue::Issue(value.clone().into()))
+                    .collect(),
+            },
+        });
+    }
+
+    if !args.filter_issue_deny.is_empty() {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Issue(IssueSortConfig::default()),
+            predicate: ValuePredicate::Deny {
+                values: args
+                    .filter_issue_deny
+                    .iter()
+                    .map(|value| DimensionValue::Issue(value.clone().into()))
+                    .collect(),
+            },
+        });
+    }
+
+    if !args.filter_comment.is_empty() {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Comment(CommentSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: args
+                    .filter_comment
+                    .iter()
+                    .map(|kind| DimensionValue::Comment(comment_kind(*kind)))
+                    .collect(),
+            },
+        });
+    }
+
+    if !args.filter_comment_deny.is_empty() {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Comment(CommentSortConfig::default()),
+            predicate: ValuePredicate::Deny {
+                values: args
+                    .filter_comment_deny
+                    .iter()
+                    .map(|kind| DimensionValue::Comment(comment_kind(*kind)))
+                    .collect(),
+            },
+        });
+    }
+
+    if args.overdue {
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Due(DueSortConfig::default()),
+            predicate: ValuePredicate::Before {
+                value: DimensionValue::Due(Some(DueDate::today())),
+            },
+        });
+    }
+
+    if let Some(raw) = &args.due_before {
+        let date = DueDate::parse_start(raw)?;
+        config.rules.push(FilterRule {
+            stage: DimensionStage::Due(DueSortConfig::default()),
+            predicate: ValuePredicate::Before {
+                value: DimensionValue::Due(Some(date)),
+            },
+        });
+    }
+
     if config.rules.is_empty() {
         Ok(None)
     } else {
@@ -254,6 +456,17 @@ pub fn resolve_filter_config(
     }
 }
 
+fn comment_kind(kind: CommentKindArg) -> CommentKind {
+    match kind {
+        CommentKindArg::Line => CommentKind::Line,
+        CommentKindArg::Block => CommentKind::Block,
+        CommentKindArg::DocLine => CommentKind::DocLine,
+        CommentKindArg::DocBlock => CommentKind::DocBlock,
+        CommentKindArg::Docstring => CommentKind::Docstring,
+        CommentKindArg::Task => CommentKind::Task,
+    }
+}
+
 fn load_dotenv(no_dotenv: bool) -> Result<(), Box<dyn Error>> {
     if no_dotenv {
         return Ok(());
@@ -278,6 +491,10 @@ fn parse_pipeline(raw: &str) -> Result<Vec<DimensionStage>, Box<dyn Error>> {
             "language" => DimensionStage::Language(LanguageSortConfig::default()),
             "path" => DimensionStage::Path(PathSortConfig::default()),
             "folder" => DimensionStage::Folder(FolderSortConfig::default()),
+            "owner" => DimensionStage::Owner(OwnerSortConfig::default()),
+            "issue" => DimensionStage::Issue(IssueSortConfig::default()),
+            "due" => DimensionStage::Due(DueSortConfig::default()),
+            "comment" => DimensionStage::Comment(CommentSortConfig::default()),
             _ => {
                 return Err(format!("unknown sort stage '{token}'").into());
             }
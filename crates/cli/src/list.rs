@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 
 use doto_core::{ScanConfig, scan_grouped};
 
-use crate::config::Config;
+use crate::config::{Config, language_map};
 use crate::messages::{MessageLevel, MessageSink, render_messages};
 use crate::progress::DeferredProgress;
 use crate::renderer::render_list;
@@ -19,7 +19,11 @@ pub fn run_list(
         config.roots
     };
 
-    let mut builder = ScanConfig::builder().roots(roots.clone());
+    let marks = config.marks.registry()?;
+    let language_map = language_map(&config.language_map)?;
+    let mut builder = ScanConfig::builder()
+        .roots(roots.clone())
+        .marks(marks.clone());
     let messages = Arc::new(Mutex::new(MessageSink::default()));
     let progress = DeferredProgress::new();
     let reporter = progress.clone();
@@ -37,6 +41,27 @@ pub fn run_list(
     if let Some(hidden) = config.hidden {
         builder = builder.include_hidden(hidden);
     }
+    if let Some(generated) = config.generated {
+        builder = builder.include_generated(generated);
+    }
+    if let Some(detection) = config.detection {
+        builder = builder.detection_mode(detection);
+    }
+    if let Some(position) = config.mark_position {
+        builder = builder.mark_position(position);
+    }
+    if let Some(multiline) = config.multiline {
+        builder = builder.multiline_marks(multiline);
+    }
+    if let Some(task_items) = config.task_items {
+        builder = builder.task_marks(task_items);
+    }
+    if let Some(disabled_code) = config.disabled_code {
+        builder = builder.disabled_code(disabled_code);
+    }
+    builder = builder
+        .languages(config.languages)
+        .language_map(language_map);
     if let Some(read_buffer_size) = config.read_buffer_size {
         builder = builder.read_buffer_size(read_buffer_size);
     }
@@ -66,7 +91,7 @@ pub fn run_list(
         return Ok(());
     }
 
-    render_list(&result.tree, &roots, config.file_header)?;
+    render_list(&result.tree, &roots, &marks, config.file_header)?;
 
     if result.tree.total() == 0 {
         if let Ok(mut sink) = messages.lock() {
@@ -149,10 +174,13 @@ fn push_skip_summary(sink: &mut MessageSink, stats: &doto_core::ScanStats) {
     if stats.skips.binary > 0 {
         parts.push((stats.skips.binary, "binary"));
     }
+    if stats.skips.generated > 0 {
+        parts.push((stats.skips.generated, "generated"));
+    }
     if parts.is_empty() {
         return;
     }
-    parts.sort_by(|a, b| b.0.cmp(&a.0));
+    parts.sort_by_key(|part| std::cmp::Reverse(part.0));
     let top = parts
         .into_iter()
         .take(3)
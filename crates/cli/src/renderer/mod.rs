@@ -7,11 +7,28 @@ use std::path::{Path, PathBuf};
 use crate::renderer::snippet::SnippetCache;
 use crate::renderer::style::{group_style_for, mark_header, mark_styled};
 use colored::Colorize;
-use doto_core::{DimensionValue, GroupNode, GroupTree, Mark};
+use doto_core::{CommentKind, DimensionValue, GroupNode, GroupTree, Mark, MarkRegistry};
 
-pub fn render_list(tree: &GroupTree, roots: &[PathBuf], file_header: bool) -> io::Result<()> {
+struct RenderContext<'a> {
+    roots: &'a [PathBuf],
+    marks: &'a MarkRegistry,
+    line_width: usize,
+    file_header: bool,
+}
+
+pub fn render_list(
+    tree: &GroupTree,
+    roots: &[PathBuf],
+    marks: &MarkRegistry,
+    file_header: bool,
+) -> io::Result<()> {
     let mut stdout = io::BufWriter::new(io::stdout());
-    let line_width = line_number_width(tree);
+    let ctx = RenderContext {
+        roots,
+        marks,
+        line_width: line_number_width(tree),
+        file_header,
+    };
     let mut snippet_cache = SnippetCache::default();
 
     if tree.total() == 0 {
@@ -22,22 +39,12 @@ pub fn render_list(tree: &GroupTree, roots: &[PathBuf], file_header: bool) -> io
         render_file_groups(
             &mut stdout,
             tree.items.as_slice(),
-            roots,
+            &ctx,
             &mut snippet_cache,
-            line_width,
             0,
-            file_header,
         )?;
     } else {
-        render_groups(
-            &mut stdout,
-            &tree.groups,
-            roots,
-            &mut snippet_cache,
-            line_width,
-            0,
-            file_header,
-        )?;
+        render_groups(&mut stdout, &tree.groups, &ctx, &mut snippet_cache, 0)?;
     }
     stdout.flush()?;
     Ok(())
@@ -46,40 +53,22 @@ pub fn render_list(tree: &GroupTree, roots: &[PathBuf], file_header: bool) -> io
 fn render_groups(
     out: &mut dyn Write,
     groups: &[GroupNode],
-    roots: &[PathBuf],
+    ctx: &RenderContext<'_>,
     snippets: &mut SnippetCache,
-    line_width: usize,
     depth: usize,
-    file_header: bool,
 ) -> io::Result<()> {
     for group in groups {
-        let label = group_label(&group.key, roots);
+        let label = group_label(&group.key, ctx.roots);
         let header = format!("{label} ({})", group.count);
         let styled_header = match &group.key {
-            DimensionValue::Mark(mark) => mark_header(mark.as_ref(), &header),
+            DimensionValue::Mark(mark) => mark_header(mark.as_ref(), &header, ctx.marks),
             _ => group_style_for(&group.key).apply(header),
         };
         writeln!(out, "{}{}", indent(depth), styled_header)?;
         if !group.groups.is_empty() {
-            render_groups(
-                out,
-                &group.groups,
-                roots,
-                snippets,
-                line_width,
-                depth + 1,
-                file_header,
-            )?;
+            render_groups(out, &group.groups, ctx, snippets, depth + 1)?;
         } else {
-            render_file_groups(
-                out,
-                &group.items,
-                roots,
-                snippets,
-                line_width,
-                depth + 1,
-                file_header,
-            )?;
+            render_file_groups(out, &group.items, ctx, snippets, depth + 1)?;
         }
     }
     Ok(())
@@ -88,25 +77,23 @@ fn render_groups(
 fn render_file_groups(
     out: &mut dyn Write,
     items: &[Mark],
-    roots: &[PathBuf],
+    ctx: &RenderContext<'_>,
     snippets: &mut SnippetCache,
-    line_width: usize,
     depth: usize,
-    file_header: bool,
 ) -> io::Result<()> {
     let mut buckets = group_by_file(items);
     for bucket in buckets.drain(..) {
-        let mark_depth = if file_header { depth + 1 } else { depth };
-        if file_header {
+        let mark_depth = if ctx.file_header { depth + 1 } else { depth };
+        if ctx.file_header {
             let header = format!(
                 "file: {} ({})",
-                relativize_path(&bucket.path, roots).display(),
+                relativize_path(&bucket.path, ctx.roots).display(),
                 bucket.items.len()
             );
             writeln!(out, "{}{}", indent(depth), header.bright_black().bold())?;
         }
         for mark in bucket.items {
-            render_mark(out, &mark, roots, snippets, line_width, mark_depth)?;
+            render_mark(out, &mark, ctx, snippets, mark_depth)?;
         }
     }
     Ok(())
@@ -115,25 +102,33 @@ fn render_file_groups(
 fn render_mark(
     out: &mut dyn Write,
     mark: &Mark,
-    roots: &[PathBuf],
+    ctx: &RenderContext<'_>,
     snippets: &mut SnippetCache,
-    line_width: usize,
     depth: usize,
 ) -> io::Result<()> {
-    let relative = relativize_path(mark.path.as_ref(), roots);
-    let styled_mark = mark_styled(mark.mark);
+    let relative = relativize_path(mark.path.as_ref(), ctx.roots);
+    let styled_mark = mark_styled(&mark.mark, ctx.marks);
+    let mut location = format!("{}:{}:{}", relative.display(), mark.line, mark.column);
+    if let Some(cell) = mark.cell {
+        location.push_str(&format!(" (cell {}, line {})", cell.index, cell.line));
+    }
+    if mark.disabled {
+        location.push_str(" (disabled)");
+    }
     writeln!(
         out,
         "{}{} {}",
         indent(depth),
-        format!("{}:{}:{}", relative.display(), mark.line, mark.column).dimmed(),
+        location.dimmed(),
         styled_mark
     )?;
 
-    let line_text = snippets.line_for(mark.path.as_ref(), mark.line);
-    let line_prefix = format!("{:>width$}", mark.line, width = line_width).dimmed();
-    let content = line_text.unwrap_or("");
-    writeln!(out, "{}{} | {}", indent(depth), line_prefix, content)?;
+    for line in mark.line..=mark.end_line.max(mark.line) {
+        let line_text = snippets.line_for(mark.path.as_ref(), line);
+        let line_prefix = format!("{:>width$}", line, width = ctx.line_width).dimmed();
+        let content = line_text.unwrap_or("");
+        writeln!(out, "{}{} | {}", indent(depth), line_prefix, content)?;
+    }
     Ok(())
 }
 
@@ -146,7 +141,7 @@ fn line_number_width(tree: &GroupTree) -> usize {
     let mut max_line = 1u32;
     if !tree.items.is_empty() {
         for mark in &tree.items {
-            max_line = max_line.max(mark.line);
+            max_line = max_line.max(mark.end_line.max(mark.line));
         }
     }
     if !tree.groups.is_empty() {
@@ -160,7 +155,7 @@ fn max_line_in_groups(groups: &[GroupNode]) -> u32 {
     let mut max_line = 1u32;
     for group in groups {
         for mark in &group.items {
-            max_line = max_line.max(mark.line);
+            max_line = max_line.max(mark.end_line.max(mark.line));
         }
         if !group.groups.is_empty() {
             max_line = max_line.max(max_line_in_groups(&group.groups));
@@ -203,6 +198,24 @@ fn group_label(key: &DimensionValue, roots: &[PathBuf]) -> String {
         DimensionValue::Language(value) => format!("language: {value}"),
         DimensionValue::Path(value) => format!("path: {}", display_group_path(value, roots)),
         DimensionValue::Folder(value) => format!("folder: {}", display_group_path(value, roots)),
+        DimensionValue::Owner(value) if value.is_empty() => "owner: (unassigned)".to_string(),
+        DimensionValue::Owner(value) => format!("owner: {value}"),
+        DimensionValue::Issue(value) if value.is_empty() => "issue: (none)".to_string(),
+        DimensionValue::Issue(value) => format!("issue: {value}"),
+        DimensionValue::Due(Some(value)) => format!("due: {value}"),
+        DimensionValue::Due(None) => "due: (none)".to_string(),
+        DimensionValue::Comment(kind) => format!("comment: {}", comment_kind_name(*kind)),
+    }
+}
+
+fn comment_kind_name(kind: CommentKind) -> &'static str {
+    match kind {
+        CommentKind::Line => "line",
+        CommentKind::Block => "block",
+        CommentKind::DocLine => "doc-line",
+        CommentKind::DocBlock => "doc-block",
+        CommentKind::Docstring => "docstring",
+        CommentKind::Task => "task",
     }
 }
 
@@ -1,6 +1,6 @@
 use colored::{ColoredString, Colorize};
 
-use doto_core::DimensionValue;
+use doto_core::{DimensionValue, MarkRegistry};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum GroupStyle {
@@ -8,6 +8,10 @@ pub(crate) enum GroupStyle {
     Language,
     Path,
     Folder,
+    Owner,
+    Issue,
+    Due,
+    Comment,
 }
 
 impl GroupStyle {
@@ -17,18 +21,24 @@ impl GroupStyle {
             GroupStyle::Language => input.magenta().bold(),
             GroupStyle::Path => input.bright_black(),
             GroupStyle::Folder => input.bright_black(),
+            GroupStyle::Owner => input.yellow().bold(),
+            GroupStyle::Issue => input.green().bold(),
+            GroupStyle::Due => input.red().bold(),
+            GroupStyle::Comment => input.cyan(),
         }
     }
 }
 
-pub(crate) fn mark_styled(mark: &str) -> ColoredString {
-    match mark {
-        "ERROR" => mark.red().bold(),
-        "WARN" => mark.yellow().bold(),
-        "FIXME" => mark.red(),
-        "TODO" => mark.cyan(),
-        "NOTE" => mark.blue(),
-        "INFO" => mark.green(),
+// Marks are coloured by their registered priority, so built-ins keep their
+// usual colours and a mark moved to another priority takes that colour.
+pub(crate) fn mark_styled(mark: &str, marks: &MarkRegistry) -> ColoredString {
+    match marks.priority(mark) {
+        Some(0) => mark.red().bold(),
+        Some(1) => mark.red(),
+        Some(2) => mark.yellow().bold(),
+        Some(3) => mark.cyan(),
+        Some(4) => mark.blue(),
+        Some(5) => mark.green(),
         _ => mark.normal(),
     }
 }
@@ -39,17 +49,20 @@ pub(crate) fn group_style_for(key: &DimensionValue) -> GroupStyle {
         DimensionValue::Language(_) => GroupStyle::Language,
         DimensionValue::Path(_) => GroupStyle::Path,
         DimensionValue::Folder(_) => GroupStyle::Folder,
+        DimensionValue::Owner(_) => GroupStyle::Owner,
+        DimensionValue::Issue(_) => GroupStyle::Issue,
+        DimensionValue::Due(_) => GroupStyle::Due,
+        DimensionValue::Comment(_) => GroupStyle::Comment,
     }
 }
 
-pub(crate) fn mark_header(mark: &str, text: &str) -> ColoredString {
-    match mark {
-        "ERROR" => text.red().bold(),
-        "WARN" => text.yellow().bold(),
-        "FIXME" => text.red().bold(),
-        "TODO" => text.cyan().bold(),
-        "NOTE" => text.blue().bold(),
-        "INFO" => text.green().bold(),
+pub(crate) fn mark_header(mark: &str, text: &str, marks: &MarkRegistry) -> ColoredString {
+    match marks.priority(mark) {
+        Some(0 | 1) => text.red().bold(),
+        Some(2) => text.yellow().bold(),
+        Some(3) => text.cyan().bold(),
+        Some(4) => text.blue().bold(),
+        Some(5) => text.green().bold(),
         _ => text.bold(),
     }
 }
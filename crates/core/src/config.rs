@@ -3,7 +3,9 @@ use std::sync::Arc;
 
 use crate::control::{CancellationToken, ProgressConfig, ProgressReporter};
 use crate::filter::FilterConfig;
+use crate::marks::{DetectionMode, MarkPosition, MarkRegistry};
 use crate::sort::{DimensionStage, SortConfig};
+use crate::syntax::{CustomSyntax, LanguageDefinition, LanguageMap};
 
 #[derive(Clone)]
 pub struct ScanConfig {
@@ -12,7 +14,16 @@ pub struct ScanConfig {
     exclude: Vec<String>,
     follow_gitignore: bool,
     include_hidden: bool,
+    include_generated: bool,
     builtin_excludes: bool,
+    marks: MarkRegistry,
+    detection_mode: DetectionMode,
+    mark_position: MarkPosition,
+    multiline_marks: bool,
+    task_marks: bool,
+    disabled_code: bool,
+    languages: Vec<CustomSyntax>,
+    language_map: LanguageMap,
     sort_config: SortConfig,
     filter_config: FilterConfig,
     max_file_size: Option<u64>,
@@ -47,10 +58,46 @@ impl ScanConfig {
         self.include_hidden
     }
 
+    pub fn include_generated(&self) -> bool {
+        self.include_generated
+    }
+
     pub fn builtin_excludes(&self) -> bool {
         self.builtin_excludes
     }
 
+    pub fn marks(&self) -> &MarkRegistry {
+        &self.marks
+    }
+
+    pub fn detection_mode(&self) -> DetectionMode {
+        self.detection_mode
+    }
+
+    pub fn mark_position(&self) -> MarkPosition {
+        self.mark_position
+    }
+
+    pub fn multiline_marks(&self) -> bool {
+        self.multiline_marks
+    }
+
+    pub fn task_marks(&self) -> bool {
+        self.task_marks
+    }
+
+    pub fn disabled_code(&self) -> bool {
+        self.disabled_code
+    }
+
+    pub(crate) fn languages(&self) -> &[CustomSyntax] {
+        &self.languages
+    }
+
+    pub fn language_map(&self) -> &LanguageMap {
+        &self.language_map
+    }
+
     pub fn sort_config(&self) -> &SortConfig {
         &self.sort_config
     }
@@ -88,7 +135,16 @@ impl std::fmt::Debug for ScanConfig {
             .field("exclude", &self.exclude)
             .field("follow_gitignore", &self.follow_gitignore)
             .field("include_hidden", &self.include_hidden)
+            .field("include_generated", &self.include_generated)
             .field("builtin_excludes", &self.builtin_excludes)
+            .field("marks", &self.marks)
+            .field("detection_mode", &self.detection_mode)
+            .field("mark_position", &self.mark_position)
+            .field("multiline_marks", &self.multiline_marks)
+            .field("task_marks", &self.task_marks)
+            .field("disabled_code", &self.disabled_code)
+            .field("languages", &self.languages)
+            .field("language_map", &self.language_map)
             .field("sort_config", &self.sort_config)
             .field("filter_config", &self.filter_config)
             .field("max_file_size", &self.max_file_size)
@@ -107,7 +163,16 @@ pub struct ScanConfigBuilder {
     exclude: Vec<String>,
     follow_gitignore: bool,
     include_hidden: bool,
+    include_generated: bool,
     builtin_excludes: bool,
+    marks: MarkRegistry,
+    detection_mode: DetectionMode,
+    mark_position: MarkPosition,
+    multiline_marks: bool,
+    task_marks: bool,
+    disabled_code: bool,
+    languages: Vec<CustomSyntax>,
+    language_map: LanguageMap,
     sort_config: SortConfig,
     filter_config: FilterConfig,
     max_file_size: Option<u64>,
@@ -125,7 +190,16 @@ impl ScanConfigBuilder {
             exclude: Vec::new(),
             follow_gitignore: true,
             include_hidden: false,
+            include_generated: false,
             builtin_excludes: true,
+            marks: MarkRegistry::builtin(),
+            detection_mode: DetectionMode::default(),
+            mark_position: MarkPosition::default(),
+            multiline_marks: true,
+            task_marks: false,
+            disabled_code: false,
+            languages: Vec::new(),
+            language_map: LanguageMap::default(),
             sort_config: SortConfig::default(),
             filter_config: FilterConfig::default(),
             max_file_size: None,
@@ -170,11 +244,90 @@ impl ScanConfigBuilder {
         self
     }
 
+    // Scans files marked generated or vendored, which are skipped by default.
+    pub fn include_generated(mut self, yes: bool) -> Self {
+        self.include_generated = yes;
+        self
+    }
+
     pub fn builtin_excludes(mut self, yes: bool) -> Self {
         self.builtin_excludes = yes;
         self
     }
 
+    pub fn marks(mut self, marks: MarkRegistry) -> Self {
+        self.marks = marks;
+        self
+    }
+
+    pub fn add_mark(mut self, name: impl AsRef<str>, priority: u8) -> Self {
+        self.marks.insert(name.as_ref(), priority);
+        self
+    }
+
+    pub fn remove_mark(mut self, name: impl AsRef<str>) -> Self {
+        self.marks.remove(name.as_ref());
+        self
+    }
+
+    pub fn add_alias(mut self, alias: impl AsRef<str>, mark: impl AsRef<str>) -> Self {
+        self.marks.add_alias(alias.as_ref(), mark.as_ref());
+        self
+    }
+
+    pub fn detection_mode(mut self, mode: DetectionMode) -> Self {
+        self.detection_mode = mode;
+        self
+    }
+
+    pub fn mark_position(mut self, position: MarkPosition) -> Self {
+        self.mark_position = position;
+        self
+    }
+
+    pub fn multiline_marks(mut self, yes: bool) -> Self {
+        self.multiline_marks = yes;
+        self
+    }
+
+    // Reports unchecked task-list items in Markdown files as TODO marks.
+    pub fn task_marks(mut self, yes: bool) -> Self {
+        self.task_marks = yes;
+        self
+    }
+
+    // Reports marks in C/C++ code disabled by the preprocessor (`#if 0`),
+    // tagged as disabled, instead of dropping them.
+    pub fn disabled_code(mut self, yes: bool) -> Self {
+        self.disabled_code = yes;
+        self
+    }
+
+    // Declared languages are consulted before the built-in table.
+    pub fn language(mut self, definition: LanguageDefinition) -> Self {
+        self.languages.push(CustomSyntax::new(&definition));
+        self
+    }
+
+    pub fn languages<I>(mut self, definitions: I) -> Self
+    where
+        I: IntoIterator<Item = LanguageDefinition>,
+    {
+        self.languages.extend(
+            definitions
+                .into_iter()
+                .map(|definition| CustomSyntax::new(&definition)),
+        );
+        self
+    }
+
+    // Mappings win over declared languages, `.gitattributes` and the built-in
+    // table.
+    pub fn language_map(mut self, language_map: LanguageMap) -> Self {
+        self.language_map = language_map;
+        self
+    }
+
     pub fn sort_config(mut self, sort_config: SortConfig) -> Self {
         self.sort_config = sort_config;
         self
@@ -230,7 +383,16 @@ impl ScanConfigBuilder {
             exclude: self.exclude,
             follow_gitignore: self.follow_gitignore,
             include_hidden: self.include_hidden,
+            include_generated: self.include_generated,
             builtin_excludes: self.builtin_excludes,
+            marks: self.marks,
+            detection_mode: self.detection_mode,
+            mark_position: self.mark_position,
+            multiline_marks: self.multiline_marks,
+            task_marks: self.task_marks,
+            disabled_code: self.disabled_code,
+            languages: self.languages,
+            language_map: self.language_map,
             sort_config: self.sort_config,
             filter_config: self.filter_config,
             max_file_size: self.max_file_size,
@@ -250,7 +412,16 @@ impl std::fmt::Debug for ScanConfigBuilder {
             .field("exclude", &self.exclude)
             .field("follow_gitignore", &self.follow_gitignore)
             .field("include_hidden", &self.include_hidden)
+            .field("include_generated", &self.include_generated)
             .field("builtin_excludes", &self.builtin_excludes)
+            .field("marks", &self.marks)
+            .field("detection_mode", &self.detection_mode)
+            .field("mark_position", &self.mark_position)
+            .field("multiline_marks", &self.multiline_marks)
+            .field("task_marks", &self.task_marks)
+            .field("disabled_code", &self.disabled_code)
+            .field("languages", &self.languages)
+            .field("language_map", &self.language_map)
             .field("sort_config", &self.sort_config)
             .field("filter_config", &self.filter_config)
             .field("max_file_size", &self.max_file_size)
@@ -65,27 +65,3 @@ pub fn apply_builtin_excludes(builder: &mut OverrideBuilder) -> Result<(), ignor
     }
     Ok(())
 }
-
-pub fn normalize_mark(input: &str) -> Option<&'static str> {
-    normalize_mark_bytes(input.as_bytes())
-}
-
-pub fn normalize_mark_bytes(input: &[u8]) -> Option<&'static str> {
-    for entry in DEFAULT_MARK_PRIORITIES {
-        let bytes = entry.mark.as_bytes();
-        if bytes.len() != input.len() {
-            continue;
-        }
-        let mut matches = true;
-        for (left, right) in bytes.iter().zip(input) {
-            if !left.eq_ignore_ascii_case(right) {
-                matches = false;
-                break;
-            }
-        }
-        if matches {
-            return Some(entry.mark);
-        }
-    }
-    None
-}
@@ -12,6 +12,8 @@ pub enum SkipReason {
     Io,
     UnsupportedSyntax,
     Binary,
+    // Generated or vendored code, by header marker or `.gitattributes`.
+    Generated,
 }
 
 pub trait ProgressReporter: Send + Sync {
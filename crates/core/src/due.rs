@@ -0,0 +1,164 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DueDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl DueDate {
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        from_days((secs / 86_400) as i64)
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    // Like `from_str`, but a month or quarter resolves to its first day. Used for
+    // bounds such as "due before 2026-Q3", which should stop at July 1st.
+    pub fn parse_start(input: &str) -> Result<Self, String> {
+        parse_date(input, Period::Start)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Period {
+    Start,
+    End,
+}
+
+impl FromStr for DueDate {
+    type Err = String;
+
+    // Accepts `YYYY-MM-DD`, `YYYY-MM` and `YYYY-Qn`; partial dates resolve to
+    // the last day of the month or quarter.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_date(input, Period::End)
+    }
+}
+
+fn parse_date(input: &str, period: Period) -> Result<DueDate, String> {
+    let invalid =
+        || format!("invalid due date '{input}' (expected YYYY-MM-DD, YYYY-MM or YYYY-Qn)");
+    let mut parts = input.trim().splitn(3, '-');
+    let year = parts
+        .next()
+        .filter(|year| year.len() == 4)
+        .and_then(|year| year.parse::<u16>().ok())
+        .ok_or_else(invalid)?;
+    let second = parts.next().ok_or_else(invalid)?;
+    let third = parts.next();
+
+    if let Some(quarter) = second
+        .strip_prefix('Q')
+        .or_else(|| second.strip_prefix('q'))
+    {
+        if third.is_some() {
+            return Err(invalid());
+        }
+        let last = match quarter {
+            "1" => 3,
+            "2" => 6,
+            "3" => 9,
+            "4" => 12,
+            _ => return Err(invalid()),
+        };
+        let (month, day) = match period {
+            Period::Start => (last - 2, 1),
+            Period::End => (last, days_in_month(year, last)),
+        };
+        return DueDate::new(year, month, day).ok_or_else(invalid);
+    }
+
+    let month = parse_two_digits(second).ok_or_else(invalid)?;
+    let day = match (third, period) {
+        (Some(day), _) => parse_two_digits(day).ok_or_else(invalid)?,
+        (None, _) if !(1..=12).contains(&month) => return Err(invalid()),
+        (None, Period::Start) => 1,
+        (None, Period::End) => days_in_month(year, month),
+    };
+    DueDate::new(year, month, day).ok_or_else(invalid)
+}
+
+impl TryFrom<String> for DueDate {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DueDate> for String {
+    fn from(value: DueDate) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for DueDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn parse_two_digits(input: &str) -> Option<u8> {
+    if input.len() != 2 || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    input.parse().ok()
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+// Civil date from days since 1970-01-01 (proleptic Gregorian calendar).
+fn from_days(days: i64) -> DueDate {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    DueDate {
+        year: year as u16,
+        month,
+        day,
+    }
+}
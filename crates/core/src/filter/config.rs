@@ -1,10 +1,13 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use crate::marks::MarkRegistry;
 use crate::model::{DimensionValue, Mark};
 use crate::sort::DimensionStage;
-use crate::utils::extract_dimension_value;
+use crate::syntax::canonical_language;
+use crate::utils::extract_dimension_values;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(default)]
@@ -17,21 +20,23 @@ impl FilterConfig {
         self.rules.is_empty()
     }
 
-    pub fn apply(&self, marks: Vec<Mark>, roots: &[PathBuf]) -> Vec<Mark> {
+    // Mark values are compared through `registry`, so aliases match the marks
+    // they resolve to in the scan.
+    pub fn apply(&self, marks: Vec<Mark>, roots: &[PathBuf], registry: &MarkRegistry) -> Vec<Mark> {
         if self.rules.is_empty() || marks.is_empty() {
             return marks;
         }
 
         marks
             .into_iter()
-            .filter(|mark| self.allows(mark, roots))
+            .filter(|mark| self.allows(mark, roots, registry))
             .collect()
     }
 
-    fn allows(&self, mark: &Mark, roots: &[PathBuf]) -> bool {
+    fn allows(&self, mark: &Mark, roots: &[PathBuf], registry: &MarkRegistry) -> bool {
         for rule in &self.rules {
-            let value = extract_dimension_value(&rule.stage, mark, roots);
-            if !rule.allows(value.as_ref()) {
+            let values = extract_dimension_values(&rule.stage, mark, roots);
+            if !rule.allows(&values, registry) {
                 return false;
             }
         }
@@ -46,8 +51,8 @@ pub struct FilterRule {
 }
 
 impl FilterRule {
-    fn allows(&self, value: Option<&DimensionValue>) -> bool {
-        self.predicate.allows(value)
+    fn allows(&self, values: &[DimensionValue], registry: &MarkRegistry) -> bool {
+        self.predicate.allows(values, registry)
     }
 }
 
@@ -56,31 +61,67 @@ impl FilterRule {
 pub enum ValuePredicate {
     Allow { values: Vec<DimensionValue> },
     Deny { values: Vec<DimensionValue> },
+    Before { value: DimensionValue },
 }
 
 impl ValuePredicate {
-    fn allows(&self, value: Option<&DimensionValue>) -> bool {
+    fn allows(&self, candidates: &[DimensionValue], registry: &MarkRegistry) -> bool {
         match self {
-            ValuePredicate::Allow { values } => {
-                value.is_some_and(|value| contains_value(values, value))
-            }
-            ValuePredicate::Deny { values } => {
-                !value.is_some_and(|value| contains_value(values, value))
+            ValuePredicate::Allow { values } => candidates
+                .iter()
+                .any(|value| contains_value(values, value, registry)),
+            ValuePredicate::Deny { values } => !candidates
+                .iter()
+                .any(|value| contains_value(values, value, registry)),
+            ValuePredicate::Before { value: bound } => {
+                candidates.iter().any(|value| value_lt(value, bound))
             }
         }
     }
 }
 
-fn contains_value(values: &[DimensionValue], value: &DimensionValue) -> bool {
-    values.iter().any(|candidate| value_eq(candidate, value))
+fn contains_value(
+    values: &[DimensionValue],
+    value: &DimensionValue,
+    registry: &MarkRegistry,
+) -> bool {
+    values
+        .iter()
+        .any(|candidate| value_eq(candidate, value, registry))
+}
+
+fn value_lt(a: &DimensionValue, b: &DimensionValue) -> bool {
+    match (a, b) {
+        (DimensionValue::Due(Some(a)), DimensionValue::Due(Some(b))) => a < b,
+        _ => false,
+    }
 }
 
-fn value_eq(a: &DimensionValue, b: &DimensionValue) -> bool {
+// Marks and languages compare by canonical name, so filtering on an alias
+// matches its target.
+fn value_eq(a: &DimensionValue, b: &DimensionValue, registry: &MarkRegistry) -> bool {
     match (a, b) {
-        (DimensionValue::Mark(a), DimensionValue::Mark(b)) => a.eq_ignore_ascii_case(b),
-        (DimensionValue::Language(a), DimensionValue::Language(b)) => a.eq_ignore_ascii_case(b),
+        (DimensionValue::Mark(a), DimensionValue::Mark(b)) => {
+            let canonical = |name: &str| registry.normalize(name).map(Arc::clone);
+            match (canonical(a), canonical(b)) {
+                (Some(a), Some(b)) => a == b,
+                _ => a.eq_ignore_ascii_case(b),
+            }
+        }
+        (DimensionValue::Language(a), DimensionValue::Language(b)) => {
+            match (canonical_language(a), canonical_language(b)) {
+                (Some(a), Some(b)) => a == b,
+                _ => a.eq_ignore_ascii_case(b),
+            }
+        }
         (DimensionValue::Path(a), DimensionValue::Path(b)) => a == b,
         (DimensionValue::Folder(a), DimensionValue::Folder(b)) => a == b,
+        (DimensionValue::Owner(a), DimensionValue::Owner(b)) => a
+            .trim_start_matches('@')
+            .eq_ignore_ascii_case(b.trim_start_matches('@')),
+        (DimensionValue::Issue(a), DimensionValue::Issue(b)) => a.eq_ignore_ascii_case(b),
+        (DimensionValue::Due(a), DimensionValue::Due(b)) => a == b,
+        (DimensionValue::Comment(a), DimensionValue::Comment(b)) => a == b,
         _ => false,
     }
 }
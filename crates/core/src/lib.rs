@@ -47,8 +47,10 @@
 mod config;
 mod constants;
 mod control;
+mod due;
 mod error;
 mod filter;
+mod marks;
 mod model;
 mod scanner;
 mod sort;
@@ -57,16 +59,25 @@ mod utils;
 
 pub use config::{ScanConfig, ScanConfigBuilder};
 pub use control::{CancellationToken, ProgressReporter, SkipReason};
+pub use due::DueDate;
 pub use error::ScanError;
 pub use filter::{FilterConfig, FilterRule, ValuePredicate};
+pub use marks::{
+    DetectionMode, MarkAlias, MarkDefinition, MarkPattern, MarkPosition, MarkRegistry,
+};
 pub use model::{
-    Dimension, DimensionValue, GroupNode, GroupTree, GroupedScanResult, Mark, ScanIssueCounts,
-    ScanResult, ScanSkipCounts, ScanStats,
+    CommentKind, Dimension, DimensionValue, GroupNode, GroupTree, GroupedScanResult, Mark,
+    NotebookCell, ScanIssueCounts, ScanResult, ScanSkipCounts, ScanStats,
 };
 pub use scanner::Scanner;
 pub use sort::{
-    DimensionStage, FolderSortConfig, LanguageOrder, LanguageSortConfig, MarkPriorityOverride,
-    MarkSortConfig, Order, PathSortConfig, SortConfig,
+    CommentSortConfig, DimensionStage, DueSortConfig, FolderSortConfig, IssueSortConfig,
+    LanguageOrder, LanguageSortConfig, MarkPriorityOverride, MarkSortConfig, Order,
+    OwnerSortConfig, PathSortConfig, SortConfig,
+};
+pub use syntax::{
+    Language, LanguageDefinition, LanguageMap, LanguageMapping, StringDefinition,
+    canonical_language,
 };
 
 pub fn scan(config: ScanConfig) -> Result<ScanResult, ScanError> {
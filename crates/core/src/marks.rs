@@ -0,0 +1,385 @@
+use std::sync::Arc;
+
+use regex::bytes::{Regex, RegexSet};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::DEFAULT_MARK_PRIORITIES;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkDefinition {
+    pub name: String,
+    pub priority: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkPattern {
+    pub name: String,
+    pub pattern: String,
+    pub priority: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkAlias {
+    pub alias: String,
+    pub mark: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMode {
+    #[default]
+    Uppercase,
+    CaseInsensitive,
+    UppercaseOrColon,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkPosition {
+    #[default]
+    Leading,
+    Anywhere,
+}
+
+// A mark is detected by its keyword, by any of its patterns, or both.
+#[derive(Clone, Debug)]
+struct MarkEntry {
+    name: Arc<str>,
+    priority: u8,
+    keyword: bool,
+    patterns: Vec<Regex>,
+}
+
+// An alternate spelling detected in place of `target`, which is the name of an
+// entry in the registry.
+#[derive(Clone, Debug)]
+struct AliasEntry {
+    name: Arc<str>,
+    target: Arc<str>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MarkRegistry {
+    entries: Vec<MarkEntry>,
+    aliases: Vec<AliasEntry>,
+    initials: [bool; 256],
+    folded_initials: [bool; 256],
+    // Every pattern at once, to skip comments none of them can match.
+    pattern_set: Option<RegexSet>,
+}
+
+impl MarkRegistry {
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+            aliases: Vec::new(),
+            initials: [false; 256],
+            folded_initials: [false; 256],
+            pattern_set: None,
+        }
+    }
+
+    pub fn builtin() -> Self {
+        let mut registry = Self::empty();
+        for entry in DEFAULT_MARK_PRIORITIES {
+            registry.insert(entry.mark, entry.priority);
+        }
+        registry
+    }
+
+    pub fn with_definitions<I>(definitions: I) -> Self
+    where
+        I: IntoIterator<Item = MarkDefinition>,
+    {
+        let mut registry = Self::empty();
+        for definition in definitions {
+            registry.insert(&definition.name, definition.priority);
+        }
+        registry
+    }
+
+    pub fn insert(&mut self, name: &str, priority: u8) {
+        let name = name.trim();
+        if name.is_empty() || !name.bytes().all(is_word_char) {
+            return;
+        }
+        if let Some(entry) = self.find_mut(name) {
+            entry.priority = priority;
+            if !entry.keyword {
+                entry.keyword = true;
+                self.rebuild_initials();
+            }
+            return;
+        }
+        self.aliases
+            .retain(|alias| !alias.name.eq_ignore_ascii_case(name));
+        self.entries.push(MarkEntry {
+            name: name.to_ascii_uppercase().into(),
+            priority,
+            keyword: true,
+            patterns: Vec::new(),
+        });
+        self.rebuild_initials();
+    }
+
+    // Adds a regex matched against comment text. Patterns for an existing mark
+    // are added alongside its keyword and keep that mark's priority.
+    pub fn add_pattern(
+        &mut self,
+        name: &str,
+        pattern: &str,
+        priority: u8,
+    ) -> Result<(), regex::Error> {
+        let regex = Regex::new(pattern)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Ok(());
+        }
+        // Build the combined set first so a failure leaves the registry as it was.
+        let pattern_set = build_pattern_set(self.pattern_sources().chain([regex.as_str()]))?;
+        if let Some(entry) = self.find_mut(name) {
+            entry.patterns.push(regex);
+        } else {
+            self.aliases
+                .retain(|alias| !alias.name.eq_ignore_ascii_case(name));
+            self.entries.push(MarkEntry {
+                name: name.to_ascii_uppercase().into(),
+                priority,
+                keyword: false,
+                patterns: vec![regex],
+            });
+        }
+        self.pattern_set = pattern_set;
+        Ok(())
+    }
+
+    // Removes a mark together with its aliases, or a single alias.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        let before = self.entries.len() + self.aliases.len();
+        self.entries
+            .retain(|entry| !entry.name.eq_ignore_ascii_case(name));
+        self.aliases.retain(|alias| {
+            !alias.name.eq_ignore_ascii_case(name) && !alias.target.eq_ignore_ascii_case(name)
+        });
+        let removed = self.entries.len() + self.aliases.len() != before;
+        if removed {
+            self.rebuild_initials();
+            // Fewer patterns never fail to combine; should they, each pattern
+            // is still tried on its own.
+            self.pattern_set = build_pattern_set(self.pattern_sources()).ok().flatten();
+        }
+        removed
+    }
+
+    // Maps `alias` onto an existing mark. An alias that names a registered mark
+    // replaces it; aliases of that mark move to the new target.
+    pub fn add_alias(&mut self, alias: &str, mark: &str) -> bool {
+        let alias = alias.trim();
+        if alias.is_empty() || !alias.bytes().all(is_word_char) {
+            return false;
+        }
+        let Some(target) = self.normalize(mark.trim()).cloned() else {
+            return false;
+        };
+        if target.eq_ignore_ascii_case(alias) {
+            return false;
+        }
+        if let Some(idx) = self
+            .entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(alias))
+        {
+            let replaced = self.entries.remove(idx).name;
+            for entry in &mut self.aliases {
+                if entry.target == replaced {
+                    entry.target = Arc::clone(&target);
+                }
+            }
+        }
+        self.aliases
+            .retain(|entry| !entry.name.eq_ignore_ascii_case(alias));
+        self.aliases.push(AliasEntry {
+            name: alias.to_ascii_uppercase().into(),
+            target,
+        });
+        self.rebuild_initials();
+        true
+    }
+
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .map(|alias| (alias.name.as_ref(), alias.target.as_ref()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_ref())
+    }
+
+    pub fn priority(&self, name: &str) -> Option<u8> {
+        self.resolve(name.as_bytes()).map(|entry| entry.priority)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.resolve(name.as_bytes()).is_some()
+    }
+
+    // Returns the canonical name for a mark or one of its aliases.
+    pub fn normalize(&self, input: &str) -> Option<&Arc<str>> {
+        self.resolve(input.as_bytes()).map(|entry| &entry.name)
+    }
+
+    pub(crate) fn has_patterns(&self) -> bool {
+        self.pattern_sources().next().is_some()
+    }
+
+    pub(crate) fn matches_pattern(&self, text: &[u8]) -> bool {
+        match &self.pattern_set {
+            Some(set) => set.is_match(text),
+            None => self.patterns().any(|(_, regex)| regex.is_match(text)),
+        }
+    }
+
+    pub(crate) fn patterns(&self) -> impl Iterator<Item = (&Arc<str>, &Regex)> {
+        self.entries.iter().flat_map(|entry| {
+            entry
+                .patterns
+                .iter()
+                .map(move |pattern| (&entry.name, pattern))
+        })
+    }
+
+    pub(crate) fn contains_initial(&self, hay: &[u8], mode: DetectionMode) -> bool {
+        let initials = match mode {
+            DetectionMode::Uppercase => &self.initials,
+            DetectionMode::CaseInsensitive | DetectionMode::UppercaseOrColon => {
+                &self.folded_initials
+            }
+        };
+        hay.iter().any(|byte| initials[*byte as usize])
+    }
+
+    // Returns the canonical mark and the length of the spelling that matched.
+    pub(crate) fn match_at(&self, input: &[u8], mode: DetectionMode) -> Option<(&Arc<str>, usize)> {
+        let names = self
+            .entries
+            .iter()
+            .filter(|entry| entry.keyword)
+            .map(|entry| (&entry.name, &entry.name))
+            .chain(
+                self.aliases
+                    .iter()
+                    .map(|alias| (&alias.name, &alias.target)),
+            );
+        for (name, canonical) in names {
+            let bytes = name.as_bytes();
+            let Some(head) = input.get(..bytes.len()) else {
+                continue;
+            };
+            let end = bytes.len();
+            if end < input.len() && is_word_char(input[end]) {
+                continue;
+            }
+            let matched = match mode {
+                DetectionMode::Uppercase => head == bytes,
+                DetectionMode::CaseInsensitive => head.eq_ignore_ascii_case(bytes),
+                DetectionMode::UppercaseOrColon => {
+                    head == bytes
+                        || (head.eq_ignore_ascii_case(bytes) && followed_by_colon(&input[end..]))
+                }
+            };
+            if matched {
+                return self
+                    .find(canonical.as_bytes())
+                    .map(|entry| (&entry.name, end));
+            }
+        }
+        None
+    }
+
+    fn find(&self, input: &[u8]) -> Option<&MarkEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name.as_bytes().eq_ignore_ascii_case(input))
+    }
+
+    fn resolve(&self, input: &[u8]) -> Option<&MarkEntry> {
+        self.find(input).or_else(|| {
+            self.aliases
+                .iter()
+                .find(|alias| alias.name.as_bytes().eq_ignore_ascii_case(input))
+                .and_then(|alias| self.find(alias.target.as_bytes()))
+        })
+    }
+
+    fn find_mut(&mut self, input: &str) -> Option<&mut MarkEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.name.eq_ignore_ascii_case(input))
+    }
+
+    fn pattern_sources(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.patterns.iter().map(Regex::as_str))
+    }
+
+    fn rebuild_initials(&mut self) {
+        self.initials = [false; 256];
+        self.folded_initials = [false; 256];
+        let names = self
+            .entries
+            .iter()
+            .filter(|entry| entry.keyword)
+            .map(|entry| &entry.name)
+            .chain(self.aliases.iter().map(|alias| &alias.name));
+        for name in names {
+            if let Some(&first) = name.as_bytes().first() {
+                self.initials[first as usize] = true;
+                self.folded_initials[first.to_ascii_uppercase() as usize] = true;
+                self.folded_initials[first.to_ascii_lowercase() as usize] = true;
+            }
+        }
+    }
+}
+
+impl Default for MarkRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+// Allows an optional `(...)` annotation between the mark and its colon.
+fn followed_by_colon(rest: &[u8]) -> bool {
+    let mut rest = rest;
+    if rest.first() == Some(&b'(') {
+        let Some(close) = memchr::memchr(b')', rest) else {
+            return false;
+        };
+        rest = &rest[close + 1..];
+    }
+    rest.first() == Some(&b':')
+}
+
+fn is_word_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+fn build_pattern_set<'a>(
+    patterns: impl Iterator<Item = &'a str>,
+) -> Result<Option<RegexSet>, regex::Error> {
+    let patterns = patterns.collect::<Vec<_>>();
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    RegexSet::new(patterns).map(Some)
+}
@@ -1,17 +1,54 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use crate::due::DueDate;
+
 #[non_exhaustive]
 #[derive(Clone, Debug)]
 pub struct Mark {
     pub path: Arc<PathBuf>,
     pub line: u32,
+    pub end_line: u32,
     pub column: u32,
-    pub mark: &'static str,
-    pub language: &'static str,
+    pub mark: Arc<str>,
+    pub raw_mark: Arc<str>,
+    pub language: Arc<str>,
+    pub embedded_language: Option<&'static str>,
+    pub message: String,
+    pub assignees: Vec<String>,
+    pub issues: Vec<String>,
+    pub due: Option<DueDate>,
+    pub comment_kind: CommentKind,
+    pub metadata: BTreeMap<String, String>,
+    // Set for marks found in a Jupyter notebook cell; `line` is still the line
+    // of the `.ipynb` file.
+    pub cell: Option<NotebookCell>,
+    // Set for marks in code the C preprocessor drops, such as `#if 0` blocks.
+    pub disabled: bool,
+}
+
+// Location of a mark inside a notebook: the zero-based cell index and the
+// one-based line within that cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct NotebookCell {
+    pub index: u32,
+    pub line: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentKind {
+    Line,
+    Block,
+    DocLine,
+    DocBlock,
+    Docstring,
+    // A Markdown task-list item (`- [ ] ...`) rather than a comment.
+    Task,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -33,6 +70,7 @@ pub struct ScanSkipCounts {
     pub io: u64,
     pub unsupported_syntax: u64,
     pub binary: u64,
+    pub generated: u64,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -61,6 +99,10 @@ pub enum Dimension {
     Language,
     Path,
     Folder,
+    Owner,
+    Issue,
+    Due,
+    Comment,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -70,6 +112,10 @@ pub enum DimensionValue {
     Language(Cow<'static, str>),
     Path(PathBuf),
     Folder(PathBuf),
+    Owner(Cow<'static, str>),
+    Issue(Cow<'static, str>),
+    Due(Option<DueDate>),
+    Comment(CommentKind),
 }
 
 #[derive(Clone, Debug)]
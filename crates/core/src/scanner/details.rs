@@ -0,0 +1,154 @@
+use crate::due::DueDate;
+use crate::model::CommentKind;
+use crate::syntax::SyntaxSpec;
+
+#[derive(Debug, Default)]
+pub struct MarkDetails {
+    pub message: String,
+    pub assignees: Vec<String>,
+    pub issues: Vec<String>,
+    pub due: Option<DueDate>,
+}
+
+pub fn parse_details(
+    line: &[u8],
+    pos: usize,
+    end: usize,
+    spec: &SyntaxSpec,
+    kind: CommentKind,
+) -> MarkDetails {
+    let end = trim_comment_end(line, pos, end, spec, kind);
+    let mut pos = skip_ws(line, pos, end);
+    let mut details = MarkDetails::default();
+
+    if pos < end && line[pos] == b'(' {
+        if let Some(close) = memchr::memchr(b')', &line[pos..end]) {
+            parse_annotation(&line[pos + 1..pos + close], &mut details);
+            pos += close + 1;
+        }
+    }
+    pos = skip_ws(line, pos, end);
+    if pos < end && line[pos] == b':' {
+        pos += 1;
+    }
+    pos = skip_ws(line, pos, end);
+
+    details.message = String::from_utf8_lossy(&line[pos..end]).into_owned();
+    details
+}
+
+fn parse_annotation(input: &[u8], details: &mut MarkDetails) {
+    let text = String::from_utf8_lossy(input);
+    for token in text
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+    {
+        if let Some(due) = due_date(token) {
+            details.due = Some(due);
+        } else if is_issue_ref(token) {
+            details.issues.push(token.to_string());
+        } else if let Some(owner) = owner_name(token) {
+            details.assignees.push(owner.to_string());
+        }
+    }
+}
+
+fn due_date(token: &str) -> Option<DueDate> {
+    let value = token
+        .get(..3)
+        .filter(|key| key.eq_ignore_ascii_case("due"))
+        .map(|_| token[3..].trim_start_matches([':', '=', ' ']))
+        .unwrap_or(token);
+    value.parse().ok()
+}
+
+// Accepts `#123`, `org/repo#123`, tracker keys such as `JIRA-88`, and URLs.
+fn is_issue_ref(token: &str) -> bool {
+    if token.starts_with("http://") || token.starts_with("https://") {
+        return true;
+    }
+    if let Some((repo, number)) = token.rsplit_once('#') {
+        return is_number(number) && repo.bytes().all(|b| is_word_byte(b) || b == b'/');
+    }
+    if let Some((key, number)) = token.rsplit_once('-') {
+        return is_number(number)
+            && key.bytes().next().is_some_and(|b| b.is_ascii_alphabetic())
+            && key.bytes().all(|b| b.is_ascii_alphanumeric());
+    }
+    false
+}
+
+fn owner_name(token: &str) -> Option<&str> {
+    let name = token.strip_prefix('@').unwrap_or(token);
+    let first = name.bytes().next()?;
+    if !first.is_ascii_alphabetic() {
+        return None;
+    }
+    name.bytes()
+        .all(|b| is_word_byte(b) || b == b'.' || b == b'-')
+        .then_some(name)
+}
+
+fn is_number(input: &str) -> bool {
+    !input.is_empty() && input.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+fn trim_comment_end(
+    line: &[u8],
+    start: usize,
+    mut end: usize,
+    spec: &SyntaxSpec,
+    kind: CommentKind,
+) -> usize {
+    while end > start && line[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    let close: Option<&[u8]> = match kind {
+        CommentKind::Block if spec.long_brackets => long_bracket_close(&line[start..end]),
+        CommentKind::Block | CommentKind::DocBlock => spec.block_comment.map(|(_, close)| close),
+        CommentKind::Docstring => [b"\"\"\"".as_slice(), b"'''".as_slice()]
+            .into_iter()
+            .find(|quote| line[start..end].ends_with(quote)),
+        CommentKind::Line | CommentKind::DocLine | CommentKind::Task => None,
+    };
+    if let Some(close) = close {
+        if line[start..end].ends_with(close) {
+            end -= close.len();
+        }
+    }
+    while end > start && line[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    end
+}
+
+fn long_bracket_close(text: &[u8]) -> Option<&[u8]> {
+    let inner = text.strip_suffix(b"]")?;
+    let level = inner.iter().rev().take_while(|&&byte| byte == b'=').count();
+    let open = inner[..inner.len() - level].strip_suffix(b"]")?;
+    Some(&text[open.len()..])
+}
+
+fn skip_ws(line: &[u8], mut pos: usize, end: usize) -> usize {
+    while pos < end && line[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+pub fn continuation_text(
+    line: &[u8],
+    range_start: usize,
+    pos: usize,
+    range_end: usize,
+    spec: &SyntaxSpec,
+    kind: CommentKind,
+) -> Option<String> {
+    let end = trim_comment_end(line, range_start, range_end, spec, kind);
+    (pos < end).then(|| String::from_utf8_lossy(&line[pos..end]).into_owned())
+}
@@ -1,13 +1,31 @@
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::config::ScanConfig;
 use crate::control::{CancellationToken, ProgressReporter, SkipReason};
-use crate::model::Mark;
+use crate::marks::{DetectionMode, MarkPosition, MarkRegistry};
+use crate::model::{CommentKind, Mark, NotebookCell};
+use crate::scanner::details::{continuation_text, parse_details};
+use crate::scanner::notebook::{is_notebook, scan_notebook};
 use crate::scanner::report::is_cancelled;
-use crate::syntax::{BlockState, SyntaxSpec, find_comment_ranges, syntax_for_path};
+use crate::syntax::{
+    BlockState, CommentRange, CustomSyntax, GitAttributes, SyntaxInfo, SyntaxSpec,
+    custom_syntax_for_path, custom_syntax_named, find_comment_ranges, long_bracket_open,
+    opens_longer_block, syntax_for_content, syntax_for_language, syntax_for_path,
+};
+
+const BINARY_PROBE_LEN: usize = 8192;
+
+// Code generators announce themselves in a comment near the top of the file.
+const GENERATED_HEADER_LINES: usize = 10;
+const GENERATED_MARKERS: &[&[u8]] = &[
+    b"@generated",
+    b"<auto-generated",
+    b"automatically generated by rust-bindgen",
+];
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ScanOutcome {
@@ -19,23 +37,54 @@ pub enum ScanOutcome {
 pub fn scan_file(
     path: &Path,
     config: &ScanConfig,
+    attributes: &GitAttributes,
     progress: &Option<Arc<dyn ProgressReporter>>,
     cancellation: &Option<CancellationToken>,
     output: &mut Vec<Mark>,
 ) -> io::Result<ScanOutcome> {
-    let Some(syntax) = syntax_for_path(path) else {
+    let syntax = syntax_for_file(path, config, attributes);
+    if syntax.is_none() && is_notebook(path) {
+        if !config.include_generated() && attributes.is_generated(path) {
+            return Ok(ScanOutcome::Skipped(SkipReason::Generated));
+        }
+        return scan_notebook(path, config, progress, cancellation, output);
+    }
+    // Only extensionless files are opened to look for a shebang or modeline;
+    // anything else the tables do not know is skipped unread.
+    if syntax.is_none() && path.extension().is_some() {
         return Ok(ScanOutcome::Skipped(SkipReason::UnsupportedSyntax));
-    };
+    }
     let mut file = File::open(path)?;
-    if is_binary_file(&mut file)? {
+    let mut head = [0u8; BINARY_PROBE_LEN];
+    let head_len = file.read(&mut head)?;
+    let head = &head[..head_len];
+    if is_binary(head) {
         return Ok(ScanOutcome::Skipped(SkipReason::Binary));
     }
+    let syntax = syntax.or_else(|| syntax_for_content(head).map(FileSyntax::Builtin));
+    let custom_strings = match syntax {
+        Some(FileSyntax::Custom(custom)) => custom.string_delims(),
+        _ => Vec::new(),
+    };
+    let custom_spec;
+    let (spec, file_language) = match syntax {
+        Some(FileSyntax::Custom(custom)) => {
+            custom_spec = custom.spec(&custom_strings);
+            (&custom_spec, custom.name())
+        }
+        Some(FileSyntax::Builtin(info)) => (info.spec, info.language),
+        None => return Ok(ScanOutcome::Skipped(SkipReason::UnsupportedSyntax)),
+    };
+    let tasks = config.task_marks() && file_language == "markdown";
+    let file_language: Arc<str> = file_language.into();
+    if !config.include_generated() && (attributes.is_generated(path) || is_generated(head, spec)) {
+        return Ok(ScanOutcome::Skipped(SkipReason::Generated));
+    }
     file.seek(SeekFrom::Start(0))?;
     let mut reader = BufReader::with_capacity(config.read_buffer_size(), file);
     let mut buf = Vec::with_capacity(4096);
     let mut line_no: u32 = 0;
-    let path = Arc::new(path.to_path_buf());
-    let mut block_state = BlockState::default();
+    let mut lines = LineScanner::new(path, config, spec, file_language, progress).with_tasks(tasks);
 
     loop {
         if is_cancelled(cancellation) {
@@ -47,37 +96,446 @@ pub fn scan_file(
             break;
         }
         line_no = line_no.saturating_add(1);
+        lines.scan_line(&buf, line_no, output);
+    }
+
+    Ok(ScanOutcome::Completed)
+}
+
+// Comment state for one run of lines in a single syntax: a whole file, or one
+// notebook cell.
+pub(crate) struct LineScanner<'a> {
+    path: Arc<PathBuf>,
+    language: Arc<str>,
+    config: &'a ScanConfig,
+    spec: &'a SyntaxSpec<'a>,
+    progress: &'a Option<Arc<dyn ProgressReporter>>,
+    block_state: BlockState,
+    hits: Vec<Hit<'a>>,
+    continuation: Option<Continuation>,
+    tasks: bool,
+}
+
+impl<'a> LineScanner<'a> {
+    pub(crate) fn new(
+        path: &Path,
+        config: &'a ScanConfig,
+        spec: &'a SyntaxSpec<'a>,
+        language: Arc<str>,
+        progress: &'a Option<Arc<dyn ProgressReporter>>,
+    ) -> Self {
+        Self {
+            path: Arc::new(path.to_path_buf()),
+            language,
+            config,
+            spec,
+            progress,
+            block_state: BlockState::default(),
+            hits: Vec::new(),
+            continuation: None,
+            tasks: false,
+        }
+    }
+
+    // Reports unchecked Markdown task-list items (`- [ ] ...`) outside code
+    // blocks as TODO marks.
+    pub(crate) fn with_tasks(mut self, tasks: bool) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    pub(crate) fn scan_line(&mut self, buf: &[u8], line_no: u32, output: &mut Vec<Mark>) {
+        self.scan_at(buf, line_no, None, output);
+    }
+
+    // `line_no` stays the line in the notebook file; `cell` locates the same
+    // line inside its cell.
+    pub(crate) fn scan_cell_line(
+        &mut self,
+        buf: &[u8],
+        line_no: u32,
+        cell: NotebookCell,
+        output: &mut Vec<Mark>,
+    ) {
+        self.scan_at(buf, line_no, Some(cell), output);
+    }
+
+    fn scan_at(
+        &mut self,
+        buf: &[u8],
+        line_no: u32,
+        cell: Option<NotebookCell>,
+        output: &mut Vec<Mark>,
+    ) {
+        let Self {
+            path,
+            language: file_language,
+            config,
+            spec,
+            progress,
+            block_state,
+            hits,
+            continuation,
+            tasks,
+        } = self;
+        let marks = config.marks();
+        let mode = config.detection_mode();
+        let position = config.mark_position();
+        let has_patterns = marks.has_patterns();
+        let was_in_block = block_state.in_comment();
+        let line_start = skip_ws(buf, 0, buf.len());
+        let mut continued = false;
 
-        find_comment_ranges(&buf, &mut block_state, syntax.spec, |start, end| {
-            if !contains_mark_initial(&buf, start, end) {
+        if *tasks && !was_in_block && !block_state.in_region() {
+            if let Some(entry) = task_mark(buf, line_no, path, file_language, marks, spec, cell) {
+                if let Some(progress) = progress.as_deref() {
+                    progress.on_match(&entry);
+                }
+                output.push(entry);
+            }
+        }
+
+        find_comment_ranges(buf, block_state, spec, |range| {
+            let CommentRange {
+                start,
+                end,
+                kind,
+                spec,
+                language,
+                disabled,
+            } = range;
+            if disabled && !config.disabled_code() {
                 return;
             }
+            let has_initial = contains_mark_initial(buf, start, end, marks, mode);
+            let has_pattern = has_patterns && marks.matches_pattern(&buf[start..end]);
+            if !has_initial && !has_pattern && continuation.is_none() {
+                return;
+            }
+            let Some(text_start) = leading_mark_pos(buf, start, end, spec, kind) else {
+                return;
+            };
 
-            if let Some(match_start) = leading_mark_pos(&buf, start, end, syntax.spec) {
-                if let Some((mark, _len)) = match_builtin_mark(&buf[match_start..end]) {
+            hits.clear();
+            if has_initial {
+                match position {
+                    MarkPosition::Leading => {
+                        if let Some((mark, len)) = marks.match_at(&buf[text_start..end], mode) {
+                            hits.push(Hit::new(text_start, mark, len));
+                        }
+                    }
+                    MarkPosition::Anywhere => {
+                        collect_marks(buf, text_start, end, marks, mode, hits);
+                    }
+                }
+            }
+            if has_pattern {
+                collect_pattern_marks(buf, text_start, end, marks, hits);
+            }
+
+            if !hits.is_empty() {
+                for idx in 0..hits.len() {
+                    let Hit { pos, mark, len, .. } = hits[idx];
+                    let message_end = hits.get(idx + 1).map_or(end, |next| next.pos);
+                    let details = parse_details(buf, pos + len, message_end, spec, kind);
                     let entry = Mark {
-                        path: Arc::clone(&path),
+                        path: Arc::clone(path),
                         line: line_no,
-                        column: (match_start + 1) as u32,
-                        mark,
-                        language: syntax.language,
+                        end_line: line_no,
+                        column: (pos + 1) as u32,
+                        mark: Arc::clone(mark),
+                        raw_mark: raw_spelling(&buf[pos..pos + len], mark),
+                        language: Arc::clone(file_language),
+                        embedded_language: language,
+                        message: details.message,
+                        assignees: details.assignees,
+                        issues: details.issues,
+                        due: details.due,
+                        comment_kind: kind,
+                        metadata: std::mem::take(&mut hits[idx].metadata),
+                        cell,
+                        disabled,
                     };
                     if let Some(progress) = progress.as_deref() {
                         progress.on_match(&entry);
                     }
                     output.push(entry);
                 }
+                *continuation = config.multiline_marks().then(|| Continuation {
+                    index: output.len() - 1,
+                    column: start,
+                    prefix: comment_prefix(buf, start, text_start, spec),
+                });
+                continued = true;
+                return;
+            }
+
+            let Some(active) = continuation.as_ref() else {
+                return;
+            };
+            let follows = match &active.prefix {
+                Some(prefix) => {
+                    start == line_start
+                        && start == active.column
+                        && comment_prefix(buf, start, text_start, spec).as_ref() == Some(prefix)
+                }
+                None => was_in_block && start == 0,
+            };
+            if !follows {
+                return;
+            }
+            if let Some(text) = continuation_text(buf, start, text_start, end, spec, kind) {
+                let entry = &mut output[active.index];
+                entry.end_line = line_no;
+                if !entry.message.is_empty() {
+                    entry.message.push(' ');
+                }
+                entry.message.push_str(&text);
+                continued = true;
             }
         });
+
+        let block_open = continuation
+            .as_ref()
+            .is_some_and(|active| active.prefix.is_some() || block_state.in_comment());
+        if !continued || !block_open {
+            *continuation = None;
+        }
     }
+}
 
-    Ok(ScanOutcome::Completed)
+#[derive(Clone, Copy)]
+enum FileSyntax<'a> {
+    Custom(&'a CustomSyntax),
+    Builtin(SyntaxInfo),
 }
 
-fn is_binary_file(file: &mut File) -> io::Result<bool> {
-    let mut buf = [0u8; 8192];
-    let read = file.read(&mut buf)?;
-    Ok(buf[..read].contains(&0))
+// Explicit mappings come first, then `.gitattributes`, declared languages and
+// finally the built-in table.
+fn syntax_for_file<'a>(
+    path: &Path,
+    config: &'a ScanConfig,
+    attributes: &GitAttributes,
+) -> Option<FileSyntax<'a>> {
+    let languages = config.languages();
+    let mapped = config.language_map().language_for(path);
+    if let Some(custom) = mapped.and_then(|name| custom_syntax_named(name, languages)) {
+        return Some(FileSyntax::Custom(custom));
+    }
+    if mapped.is_none() {
+        if let Some(name) = attributes.language_for(path) {
+            if let Some(custom) = custom_syntax_named(&name, languages) {
+                return Some(FileSyntax::Custom(custom));
+            }
+            if let Some(info) = syntax_for_language(&name) {
+                return Some(FileSyntax::Builtin(info));
+            }
+        }
+        if let Some(custom) = custom_syntax_for_path(path, languages) {
+            return Some(FileSyntax::Custom(custom));
+        }
+    }
+    syntax_for_path(path, config.language_map()).map(FileSyntax::Builtin)
+}
+
+struct Hit<'a> {
+    pos: usize,
+    mark: &'a Arc<str>,
+    len: usize,
+    metadata: BTreeMap<String, String>,
+}
+
+impl<'a> Hit<'a> {
+    fn new(pos: usize, mark: &'a Arc<str>, len: usize) -> Self {
+        Self {
+            pos,
+            mark,
+            len,
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
+// Tracks the most recent mark so following comment lines can extend it. Line
+// comments continue while the same comment prefix sits at the same column;
+// block comments (no prefix) continue until the block closes.
+struct Continuation {
+    index: usize,
+    column: usize,
+    prefix: Option<Vec<u8>>,
+}
+
+fn raw_spelling(raw: &[u8], mark: &Arc<str>) -> Arc<str> {
+    if raw == mark.as_bytes() {
+        Arc::clone(mark)
+    } else {
+        String::from_utf8_lossy(raw).into()
+    }
+}
+
+fn comment_prefix(
+    line: &[u8],
+    start: usize,
+    text_start: usize,
+    spec: &SyntaxSpec,
+) -> Option<Vec<u8>> {
+    spec.line_comment_at(line, start)?;
+    let prefix = &line[start..text_start];
+    let len = prefix
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(0, |idx| idx + 1);
+    Some(prefix[..len].to_vec())
+}
+
+fn collect_marks<'a>(
+    line: &[u8],
+    start: usize,
+    end: usize,
+    marks: &'a MarkRegistry,
+    mode: DetectionMode,
+    hits: &mut Vec<Hit<'a>>,
+) {
+    let mut pos = start;
+    while pos < end {
+        let at_boundary = pos == start || !is_word_char(line[pos - 1]);
+        if at_boundary && marks.contains_initial(&line[pos..=pos], mode) {
+            if let Some((mark, len)) = marks.match_at(&line[pos..end], mode) {
+                hits.push(Hit::new(pos, mark, len));
+                pos += len;
+                continue;
+            }
+        }
+        pos += 1;
+    }
+}
+
+// Pattern matches run over the comment text and merge with keyword hits; when
+// two hits overlap the earlier, then longer, one wins.
+fn collect_pattern_marks<'a>(
+    line: &[u8],
+    start: usize,
+    end: usize,
+    marks: &'a MarkRegistry,
+    hits: &mut Vec<Hit<'a>>,
+) {
+    let text = &line[start..end];
+    for (mark, pattern) in marks.patterns() {
+        for captures in pattern.captures_iter(text) {
+            let Some(found) = captures.get(0).filter(|found| !found.is_empty()) else {
+                continue;
+            };
+            let mut hit = Hit::new(start + found.start(), mark, found.len());
+            for name in pattern.capture_names().flatten() {
+                if let Some(value) = captures.name(name) {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    hit.metadata.insert(name.to_string(), value);
+                }
+            }
+            hits.push(hit);
+        }
+    }
+    hits.sort_by(|a, b| a.pos.cmp(&b.pos).then(b.len.cmp(&a.len)));
+    let mut covered = 0;
+    hits.retain(|hit| {
+        let keep = hit.pos >= covered;
+        if keep {
+            covered = hit.pos + hit.len;
+        }
+        keep
+    });
+}
+
+fn task_mark(
+    buf: &[u8],
+    line_no: u32,
+    path: &Arc<PathBuf>,
+    language: &Arc<str>,
+    marks: &MarkRegistry,
+    spec: &SyntaxSpec,
+    cell: Option<NotebookCell>,
+) -> Option<Mark> {
+    let (pos, text_start) = unchecked_task(buf)?;
+    let mark = marks.normalize("TODO")?;
+    let details = parse_details(buf, text_start, buf.len(), spec, CommentKind::Task);
+    Some(Mark {
+        path: Arc::clone(path),
+        line: line_no,
+        end_line: line_no,
+        column: (pos + 1) as u32,
+        mark: Arc::clone(mark),
+        raw_mark: Arc::clone(mark),
+        language: Arc::clone(language),
+        embedded_language: None,
+        message: details.message,
+        assignees: details.assignees,
+        issues: details.issues,
+        due: details.due,
+        comment_kind: CommentKind::Task,
+        metadata: BTreeMap::new(),
+        cell,
+        disabled: false,
+    })
+}
+
+// `- [ ] text`, `* [ ] text`, `1. [ ] text`: returns the checkbox position
+// and the start of the item text.
+fn unchecked_task(line: &[u8]) -> Option<(usize, usize)> {
+    let mut pos = skip_ws(line, 0, line.len());
+    match line.get(pos)? {
+        b'-' | b'*' | b'+' => pos += 1,
+        byte if byte.is_ascii_digit() => {
+            while line.get(pos).is_some_and(u8::is_ascii_digit) {
+                pos += 1;
+            }
+            if !matches!(line.get(pos), Some(b'.' | b')')) {
+                return None;
+            }
+            pos += 1;
+        }
+        _ => return None,
+    }
+    let checkbox = skip_ws(line, pos, line.len());
+    if checkbox == pos || !starts_with(line, b"[ ]", checkbox) {
+        return None;
+    }
+    let text_start = checkbox + 3;
+    if line
+        .get(text_start)
+        .is_some_and(|byte| !byte.is_ascii_whitespace())
+    {
+        return None;
+    }
+    Some((checkbox, skip_ws(line, text_start, line.len())))
+}
+
+pub(crate) fn is_binary(head: &[u8]) -> bool {
+    head.contains(&0)
+}
+
+// A comment that starts a line and holds a marker, or reads
+// `Code generated ... DO NOT EDIT.` as Go prescribes.
+pub(crate) fn is_generated(head: &[u8], spec: &SyntaxSpec) -> bool {
+    head.split(|byte| *byte == b'\n')
+        .take(GENERATED_HEADER_LINES)
+        .filter_map(|line| header_comment_text(line.trim_ascii(), spec))
+        .any(|text| {
+            let text = text.trim_ascii();
+            (text.starts_with(b"Code generated ") && text.ends_with(b" DO NOT EDIT."))
+                || GENERATED_MARKERS
+                    .iter()
+                    .any(|marker| text.windows(marker.len()).any(|window| window == *marker))
+        })
+}
+
+// The text after a comment token at the start of `line`, including the `*`
+// that continues a block comment.
+fn header_comment_text<'a>(line: &'a [u8], spec: &SyntaxSpec) -> Option<&'a [u8]> {
+    if let Some(token) = spec.line_comment_at(line, 0) {
+        return Some(&line[token.len()..]);
+    }
+    let (open, _) = spec.block_comment?;
+    line.strip_prefix(open).or_else(|| line.strip_prefix(b"*"))
 }
 
 fn leading_mark_pos(
@@ -85,17 +543,50 @@ fn leading_mark_pos(
     range_start: usize,
     range_end: usize,
     spec: &SyntaxSpec,
+    kind: CommentKind,
 ) -> Option<usize> {
     let mut pos = range_start;
     let mut allow_block_marker = false;
 
-    if let Some(token) = spec.line_comment {
-        if starts_with(line, token, pos) {
+    if kind == CommentKind::Docstring {
+        pos = skip_ws(line, pos, range_end);
+        if starts_with(line, b"\"\"\"", pos) || starts_with(line, b"'''", pos) {
+            pos += 3;
+        }
+        pos = skip_ws(line, pos, range_end);
+        return (pos < range_end).then_some(pos);
+    }
+
+    let doc_tokens = match kind {
+        CommentKind::DocLine => spec.doc_line,
+        CommentKind::DocBlock => spec.doc_block,
+        _ => &[],
+    };
+    if let Some(token) = doc_tokens
+        .iter()
+        .find(|token| starts_with(line, token, pos))
+    {
+        pos = skip_ws(line, pos + token.len(), range_end);
+        return (pos < range_end).then_some(pos);
+    }
+
+    if let Some(token) = spec.line_comment_at(line, pos) {
+        if !opens_longer_block(line, pos, token, spec) {
             pos += token.len();
+            if spec.long_brackets {
+                if let Some((_, consumed)) = long_bracket_open(line, pos) {
+                    pos += consumed;
+                }
+            }
             if token.first() == Some(&b'/') {
                 while pos < range_end && (line[pos] == b'/' || line[pos] == b'!') {
                     pos += 1;
                 }
+            } else if matches!(token, b";" | b"%") {
+                // `;;;` and `%%` headings are written as one comment token.
+                while pos < range_end && line[pos] == token[0] {
+                    pos += 1;
+                }
             } else if token.first() == Some(&b'#') && pos < range_end && line[pos] == b'!' {
                 pos += 1;
             }
@@ -109,6 +600,7 @@ fn leading_mark_pos(
             pos += start.len();
             allow_block_marker = true;
         } else if range_start == 0 {
+            pos = skip_ws(line, pos, range_end);
             allow_block_marker = true;
         }
     }
@@ -120,6 +612,10 @@ fn leading_mark_pos(
     (pos < range_end).then_some(pos)
 }
 
+fn is_word_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
 fn skip_ws(line: &[u8], mut pos: usize, end: usize) -> usize {
     while pos < end && line[pos].is_ascii_whitespace() {
         pos += 1;
@@ -131,40 +627,15 @@ fn starts_with(line: &[u8], needle: &[u8], idx: usize) -> bool {
     idx + needle.len() <= line.len() && &line[idx..idx + needle.len()] == needle
 }
 
-fn contains_mark_initial(line: &[u8], start: usize, end: usize) -> bool {
+fn contains_mark_initial(
+    line: &[u8],
+    start: usize,
+    end: usize,
+    marks: &MarkRegistry,
+    mode: DetectionMode,
+) -> bool {
     if start >= end || end > line.len() {
         return false;
     }
-    let hay = &line[start..end];
-    memchr::memchr(b'E', hay).is_some()
-        || memchr::memchr(b'W', hay).is_some()
-        || memchr::memchr(b'F', hay).is_some()
-        || memchr::memchr(b'T', hay).is_some()
-        || memchr::memchr(b'N', hay).is_some()
-        || memchr::memchr(b'I', hay).is_some()
-}
-
-fn match_builtin_mark(input: &[u8]) -> Option<(&'static str, usize)> {
-    const MARKS: [(&str, &[u8]); 6] = [
-        ("ERROR", b"ERROR"),
-        ("WARN", b"WARN"),
-        ("FIXME", b"FIXME"),
-        ("TODO", b"TODO"),
-        ("NOTE", b"NOTE"),
-        ("INFO", b"INFO"),
-    ];
-
-    for (name, bytes) in MARKS {
-        if input.starts_with(bytes) {
-            let end = bytes.len();
-            if end == input.len() || !is_word_char(input[end]) {
-                return Some((name, end));
-            }
-        }
-    }
-    None
-}
-
-fn is_word_char(byte: u8) -> bool {
-    byte.is_ascii_alphanumeric() || byte == b'_'
+    marks.contains_initial(&line[start..end], mode)
 }
@@ -1,4 +1,6 @@
+mod details;
 mod file;
+mod notebook;
 mod report;
 mod stats;
 mod walk;
@@ -19,6 +21,7 @@ use crate::scanner::report::{
 use crate::scanner::stats::{ScanCounters, WarningKind};
 use crate::scanner::walk::build_walk_builder;
 use crate::sort::{apply_sort_pipeline, build_group_tree};
+use crate::syntax::GitAttributes;
 use ignore::WalkState;
 
 pub struct Scanner {
@@ -36,12 +39,17 @@ impl Scanner {
 
     pub fn scan(&self) -> Result<ScanResult, ScanError> {
         let output = self.scan_raw()?;
-        let filtered = self
-            .config
-            .filter_config()
-            .apply(output.marks, self.config.roots());
-        let sorted_marks =
-            apply_sort_pipeline(filtered, self.config.sort_config(), self.config.roots());
+        let filtered = self.config.filter_config().apply(
+            output.marks,
+            self.config.roots(),
+            self.config.marks(),
+        );
+        let sorted_marks = apply_sort_pipeline(
+            filtered,
+            self.config.sort_config(),
+            self.config.roots(),
+            self.config.marks(),
+        );
 
         Ok(ScanResult {
             marks: sorted_marks,
@@ -51,11 +59,17 @@ impl Scanner {
 
     pub fn scan_grouped(&self) -> Result<GroupedScanResult, ScanError> {
         let output = self.scan_raw()?;
-        let filtered = self
-            .config
-            .filter_config()
-            .apply(output.marks, self.config.roots());
-        let tree = build_group_tree(filtered, self.config.sort_config(), self.config.roots());
+        let filtered = self.config.filter_config().apply(
+            output.marks,
+            self.config.roots(),
+            self.config.marks(),
+        );
+        let tree = build_group_tree(
+            filtered,
+            self.config.sort_config(),
+            self.config.roots(),
+            self.config.marks(),
+        );
 
         Ok(GroupedScanResult {
             tree,
@@ -82,6 +96,7 @@ impl Scanner {
 
             let builder = build_walk_builder(&self.config, root)?;
             let walker = builder.build_parallel();
+            let attributes = Arc::new(GitAttributes::load(root));
 
             let counters_ref = Arc::clone(&counters);
             let output_ref = Arc::clone(&output);
@@ -91,6 +106,7 @@ impl Scanner {
 
             walker.run(move || {
                 let config = config.clone();
+                let attributes = Arc::clone(&attributes);
                 let progress = progress.clone();
                 let cancellation = cancellation.clone();
                 let counters = Arc::clone(&counters_ref);
@@ -143,7 +159,14 @@ impl Scanner {
                     }
 
                     let before = local.marks.len();
-                    match scan_file(path, &config, &progress, &cancellation, &mut local.marks) {
+                    match scan_file(
+                        path,
+                        &config,
+                        &attributes,
+                        &progress,
+                        &cancellation,
+                        &mut local.marks,
+                    ) {
                         Ok(ScanOutcome::Completed) => {
                             counters.files_scanned.fetch_add(1, Ordering::Relaxed);
                             report_file_scanned(&progress, path);
@@ -194,6 +217,7 @@ impl Scanner {
                 io: counters.skip_io.load(Ordering::Relaxed),
                 unsupported_syntax: counters.skip_unsupported_syntax.load(Ordering::Relaxed),
                 binary: counters.skip_binary.load(Ordering::Relaxed),
+                generated: counters.skip_generated.load(Ordering::Relaxed),
             },
             issues: ScanIssueCounts {
                 walk_errors: counters.warn_walk.load(Ordering::Relaxed),
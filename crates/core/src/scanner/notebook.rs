@@ -0,0 +1,263 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use crate::config::ScanConfig;
+use crate::control::{CancellationToken, ProgressReporter, SkipReason};
+use crate::model::{Mark, NotebookCell};
+use crate::scanner::file::{LineScanner, ScanOutcome, is_binary, is_generated};
+use crate::scanner::report::is_cancelled;
+use crate::syntax::syntax_for_language;
+
+const DEFAULT_KERNEL_LANGUAGE: &str = "python";
+
+#[derive(Debug, Deserialize)]
+struct Notebook<'a> {
+    #[serde(default, borrow)]
+    cells: Vec<Cell<'a>>,
+    #[serde(default)]
+    metadata: NotebookMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NotebookMetadata {
+    language_info: Option<LanguageInfo>,
+    kernelspec: Option<KernelSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageInfo {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KernelSpec {
+    language: Option<String>,
+}
+
+// nbformat stores cell source either as a list of lines or as one string. It
+// is kept raw so each line can be traced back to its place in the file.
+#[derive(Debug, Deserialize)]
+struct Cell<'a> {
+    cell_type: String,
+    #[serde(default, borrow)]
+    source: Option<&'a RawValue>,
+}
+
+pub(crate) fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+// Code cells are scanned with the kernel language's syntax and Markdown cells
+// with Markdown's. Marks keep the line in the `.ipynb` file that holds the
+// source line, so they stay navigable, and record the cell position.
+pub(crate) fn scan_notebook(
+    path: &Path,
+    config: &ScanConfig,
+    progress: &Option<Arc<dyn ProgressReporter>>,
+    cancellation: &Option<CancellationToken>,
+    output: &mut Vec<Mark>,
+) -> io::Result<ScanOutcome> {
+    let bytes = fs::read(path)?;
+    if is_binary(&bytes) {
+        return Ok(ScanOutcome::Skipped(SkipReason::Binary));
+    }
+    let Ok(raw) = std::str::from_utf8(&bytes) else {
+        return Ok(ScanOutcome::Skipped(SkipReason::UnsupportedSyntax));
+    };
+    let Ok(notebook) = serde_json::from_str::<Notebook>(raw) else {
+        return Ok(ScanOutcome::Skipped(SkipReason::UnsupportedSyntax));
+    };
+    let kernel = notebook
+        .metadata
+        .language_info
+        .and_then(|info| info.name)
+        .or_else(|| notebook.metadata.kernelspec.and_then(|spec| spec.language))
+        .unwrap_or_else(|| DEFAULT_KERNEL_LANGUAGE.to_string());
+    let code = syntax_for_language(&kernel);
+    let markdown = syntax_for_language("markdown");
+    let mut lines_seen = LineCounter::default();
+
+    for (index, cell) in notebook.cells.iter().enumerate() {
+        let (syntax, tasks) = match cell.cell_type.as_str() {
+            "code" => (code, false),
+            "markdown" => (markdown, config.task_marks()),
+            _ => (None, false),
+        };
+        let Some(syntax) = syntax else {
+            continue;
+        };
+        let lines = match cell.source {
+            Some(source) => match source_lines(raw, source) {
+                Some(lines) => lines,
+                None => return Ok(ScanOutcome::Skipped(SkipReason::UnsupportedSyntax)),
+            },
+            None => Vec::new(),
+        };
+        // Generators mark notebooks in their first cell.
+        if index == 0 && !config.include_generated() {
+            let head = lines
+                .iter()
+                .map(|line| line.text.as_str())
+                .collect::<String>();
+            if is_generated(head.as_bytes(), syntax.spec) {
+                return Ok(ScanOutcome::Skipped(SkipReason::Generated));
+            }
+        }
+        let mut scanner =
+            LineScanner::new(path, config, syntax.spec, syntax.language.into(), progress)
+                .with_tasks(tasks);
+        for (offset, source) in lines.iter().enumerate() {
+            if is_cancelled(cancellation) {
+                return Ok(ScanOutcome::Cancelled);
+            }
+            let cell = NotebookCell {
+                index: index as u32,
+                line: offset as u32 + 1,
+            };
+            let file_line = lines_seen.advance(raw, source.start);
+            let before = output.len();
+            scanner.scan_cell_line(source.text.as_bytes(), file_line, cell, output);
+            for mark in &mut output[before..] {
+                let decoded = mark.column.saturating_sub(1) as usize;
+                mark.column = (source.raw_offset(raw, decoded) - lines_seen.line_start + 1) as u32;
+            }
+        }
+    }
+
+    Ok(ScanOutcome::Completed)
+}
+
+// One decoded source line and the span of its escaped text in the raw JSON.
+struct SourceLine {
+    text: String,
+    start: usize,
+}
+
+impl SourceLine {
+    // Raw offset of the byte `decoded` bytes into the line.
+    fn raw_offset(&self, raw: &str, decoded: usize) -> usize {
+        let mut pos = self.start;
+        let mut seen = 0;
+        while seen < decoded {
+            let Some((ch, next)) = decode_char(raw, pos) else {
+                break;
+            };
+            seen += ch.len_utf8();
+            pos = next;
+        }
+        pos
+    }
+}
+
+// Tracks the file line of raw offsets visited in increasing order.
+#[derive(Default)]
+struct LineCounter {
+    pos: usize,
+    line: u32,
+    line_start: usize,
+}
+
+impl LineCounter {
+    fn advance(&mut self, raw: &str, pos: usize) -> u32 {
+        let skipped = &raw.as_bytes()[self.pos..pos.max(self.pos)];
+        self.line += memchr::memchr_iter(b'\n', skipped).count() as u32;
+        if let Some(newline) = memchr::memrchr(b'\n', skipped) {
+            self.line_start = self.pos + newline + 1;
+        }
+        self.pos = pos.max(self.pos);
+        self.line + 1
+    }
+}
+
+// Decoded lines of a `source` value, or `None` when it is neither a string
+// nor a list of strings.
+fn source_lines(raw: &str, source: &RawValue) -> Option<Vec<SourceLine>> {
+    let text = source.get();
+    let parts = if text.starts_with('[') {
+        serde_json::from_str::<Vec<&RawValue>>(text).ok()?
+    } else {
+        vec![source]
+    };
+    let mut lines = Vec::new();
+    for part in parts {
+        let part = part.get();
+        if !part.starts_with('"') {
+            return None;
+        }
+        // Borrowed values point into `raw`, so their offset is where they sit.
+        let start = part.as_ptr() as usize - raw.as_ptr() as usize;
+        split_lines(raw, start + 1, start + part.len() - 1, &mut lines);
+    }
+    Some(lines)
+}
+
+// Splits an escaped string at each decoded newline, keeping the newline.
+fn split_lines(raw: &str, start: usize, end: usize, lines: &mut Vec<SourceLine>) {
+    let mut line = SourceLine {
+        text: String::new(),
+        start,
+    };
+    let mut pos = start;
+    while pos < end {
+        let Some((ch, next)) = decode_char(raw, pos) else {
+            break;
+        };
+        line.text.push(ch);
+        pos = next;
+        if ch == '\n' {
+            let done = std::mem::replace(
+                &mut line,
+                SourceLine {
+                    text: String::new(),
+                    start: pos,
+                },
+            );
+            lines.push(done);
+        }
+    }
+    if !line.text.is_empty() {
+        lines.push(line);
+    }
+}
+
+// The character at `pos` inside a JSON string and the offset after it.
+fn decode_char(raw: &str, pos: usize) -> Option<(char, usize)> {
+    let bytes = raw.as_bytes();
+    if bytes.get(pos) != Some(&b'\\') {
+        let ch = raw.get(pos..)?.chars().next()?;
+        return Some((ch, pos + ch.len_utf8()));
+    }
+    let ch = match bytes.get(pos + 1)? {
+        b'b' => '\u{8}',
+        b'f' => '\u{c}',
+        b'n' => '\n',
+        b'r' => '\r',
+        b't' => '\t',
+        b'u' => {
+            let high = hex4(raw, pos + 2)?;
+            if (0xd800..0xdc00).contains(&high) && raw.get(pos + 6..pos + 8) == Some("\\u") {
+                if let Some(low) = hex4(raw, pos + 8).filter(|low| (0xdc00..0xe000).contains(low)) {
+                    let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                    return Some((char::from_u32(code)?, pos + 12));
+                }
+            }
+            return Some((
+                char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER),
+                pos + 6,
+            ));
+        }
+        other => *other as char,
+    };
+    Some((ch, pos + 2))
+}
+
+fn hex4(raw: &str, pos: usize) -> Option<u32> {
+    u32::from_str_radix(raw.get(pos..pos + 4)?, 16).ok()
+}
@@ -15,6 +15,7 @@ pub struct ScanCounters {
     pub skip_io: AtomicU64,
     pub skip_unsupported_syntax: AtomicU64,
     pub skip_binary: AtomicU64,
+    pub skip_generated: AtomicU64,
     pub warn_walk: AtomicU64,
     pub warn_metadata: AtomicU64,
     pub warn_io: AtomicU64,
@@ -34,6 +35,7 @@ impl Default for ScanCounters {
             skip_io: AtomicU64::new(0),
             skip_unsupported_syntax: AtomicU64::new(0),
             skip_binary: AtomicU64::new(0),
+            skip_generated: AtomicU64::new(0),
             warn_walk: AtomicU64::new(0),
             warn_metadata: AtomicU64::new(0),
             warn_io: AtomicU64::new(0),
@@ -71,6 +73,10 @@ impl ScanCounters {
                 self.skip_binary.fetch_add(1, Ordering::Relaxed);
                 self.skipped_expected.fetch_add(1, Ordering::Relaxed);
             }
+            SkipReason::Generated => {
+                self.skip_generated.fetch_add(1, Ordering::Relaxed);
+                self.skipped_expected.fetch_add(1, Ordering::Relaxed);
+            }
         }
     }
 
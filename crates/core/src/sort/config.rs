@@ -30,6 +30,10 @@ pub enum DimensionStage {
     Language(LanguageSortConfig),
     Path(PathSortConfig),
     Folder(FolderSortConfig),
+    Owner(OwnerSortConfig),
+    Issue(IssueSortConfig),
+    Due(DueSortConfig),
+    Comment(CommentSortConfig),
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -88,6 +92,30 @@ impl Default for FolderSortConfig {
     }
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OwnerSortConfig {
+    pub order: Order,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IssueSortConfig {
+    pub order: Order,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DueSortConfig {
+    pub order: Order,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommentSortConfig {
+    pub order: Order,
+}
+
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Order {
@@ -6,8 +6,9 @@ mod tree;
 
 #[allow(unused_imports)]
 pub use config::{
-    DimensionStage, FolderSortConfig, LanguageOrder, LanguageSortConfig, MarkPriorityOverride,
-    MarkSortConfig, Order, PathSortConfig, SortConfig,
+    CommentSortConfig, DimensionStage, DueSortConfig, FolderSortConfig, IssueSortConfig,
+    LanguageOrder, LanguageSortConfig, MarkPriorityOverride, MarkSortConfig, Order,
+    OwnerSortConfig, PathSortConfig, SortConfig,
 };
 pub use pipeline::apply_sort_pipeline;
 pub use tree::build_group_tree;
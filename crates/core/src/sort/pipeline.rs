@@ -1,21 +1,34 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::marks::MarkRegistry;
 use crate::model::Mark;
 use crate::sort::config::{DimensionStage, SortConfig};
 use crate::sort::stages::group_for_stage;
 
-pub fn apply_sort_pipeline(marks: Vec<Mark>, config: &SortConfig, roots: &[PathBuf]) -> Vec<Mark> {
+pub fn apply_sort_pipeline(
+    marks: Vec<Mark>,
+    config: &SortConfig,
+    roots: &[PathBuf],
+    registry: &MarkRegistry,
+) -> Vec<Mark> {
     if marks.len() <= 1 || config.pipeline.is_empty() {
         return marks;
     }
     let mut output = Vec::with_capacity(marks.len());
-    sort_recursive(&config.pipeline, roots, marks, &mut output);
+    sort_recursive(&config.pipeline, roots, registry, marks, &mut output);
+    // Owner and issue stages put a mark in every group it names; a flat list
+    // keeps it at its first place only.
+    let mut seen = HashSet::new();
+    output.retain(|mark| seen.insert((Arc::clone(&mark.path), mark.line, mark.column, mark.cell)));
     output
 }
 
 fn sort_recursive(
     stages: &[DimensionStage],
     roots: &[PathBuf],
+    registry: &MarkRegistry,
     items: Vec<Mark>,
     output: &mut Vec<Mark>,
 ) {
@@ -25,9 +38,9 @@ fn sort_recursive(
     }
 
     let stage = &stages[0];
-    let mut groups = group_for_stage(stage, items, roots);
+    let mut groups = group_for_stage(stage, items, roots, registry);
 
     for group in groups.drain(..) {
-        sort_recursive(&stages[1..], roots, group.items, output);
+        sort_recursive(&stages[1..], roots, registry, group.items, output);
     }
 }
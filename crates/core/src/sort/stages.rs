@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use crate::constants::{DEFAULT_MARK_PRIORITIES, normalize_mark};
-use crate::model::{DimensionValue, Mark};
+use crate::due::DueDate;
+use crate::marks::MarkRegistry;
+use crate::model::{CommentKind, DimensionValue, Mark};
 use crate::sort::config::{
-    DimensionStage, FolderSortConfig, LanguageOrder, LanguageSortConfig, MarkPriorityOverride,
-    MarkSortConfig, Order, PathSortConfig,
+    CommentSortConfig, DimensionStage, DueSortConfig, FolderSortConfig, IssueSortConfig,
+    LanguageOrder, LanguageSortConfig, MarkPriorityOverride, MarkSortConfig, Order,
+    OwnerSortConfig, PathSortConfig,
 };
 use crate::sort::group::Group;
 use crate::utils::folder_key;
@@ -14,27 +17,29 @@ pub(crate) fn group_for_stage(
     stage: &DimensionStage,
     items: Vec<Mark>,
     roots: &[PathBuf],
+    marks: &MarkRegistry,
 ) -> Vec<Group> {
     match stage {
-        DimensionStage::Mark(config) => group_by_mark(items, config),
+        DimensionStage::Mark(config) => group_by_mark(items, config, marks),
         DimensionStage::Language(config) => group_by_language(items, config),
         DimensionStage::Path(config) => group_by_path(items, config),
         DimensionStage::Folder(config) => group_by_folder(items, config, roots),
+        DimensionStage::Owner(config) => group_by_owner(items, config),
+        DimensionStage::Issue(config) => group_by_issue(items, config),
+        DimensionStage::Due(config) => group_by_due(items, config),
+        DimensionStage::Comment(config) => group_by_comment(items, config),
     }
 }
 
-fn group_by_mark(items: Vec<Mark>, config: &MarkSortConfig) -> Vec<Group> {
-    let mut map: HashMap<&'static str, Vec<Mark>> = HashMap::new();
+fn group_by_mark(items: Vec<Mark>, config: &MarkSortConfig, marks: &MarkRegistry) -> Vec<Group> {
+    let mut map: HashMap<Arc<str>, Vec<Mark>> = HashMap::new();
     for mark in items {
-        let Some(kind) = normalize_mark(mark.mark) else {
-            continue;
-        };
-        map.entry(kind).or_default().push(mark);
+        map.entry(Arc::clone(&mark.mark)).or_default().push(mark);
     }
     let mut groups = map
         .into_iter()
         .map(|(key, items)| Group {
-            key: DimensionValue::Mark(key.into()),
+            key: DimensionValue::Mark(key.to_string().into()),
             items,
         })
         .collect::<Vec<_>>();
@@ -42,8 +47,8 @@ fn group_by_mark(items: Vec<Mark>, config: &MarkSortConfig) -> Vec<Group> {
         let (DimensionValue::Mark(a_key), DimensionValue::Mark(b_key)) = (&a.key, &b.key) else {
             return std::cmp::Ordering::Equal;
         };
-        let a_prio = mark_priority(a_key.as_ref(), &config.overrides).unwrap_or(u8::MAX);
-        let b_prio = mark_priority(b_key.as_ref(), &config.overrides).unwrap_or(u8::MAX);
+        let a_prio = mark_priority(a_key.as_ref(), &config.overrides, marks).unwrap_or(u8::MAX);
+        let b_prio = mark_priority(b_key.as_ref(), &config.overrides, marks).unwrap_or(u8::MAX);
         a_prio
             .cmp(&b_prio)
             .then_with(|| a_key.as_ref().cmp(b_key.as_ref()))
@@ -52,14 +57,16 @@ fn group_by_mark(items: Vec<Mark>, config: &MarkSortConfig) -> Vec<Group> {
 }
 
 fn group_by_language(items: Vec<Mark>, config: &LanguageSortConfig) -> Vec<Group> {
-    let mut map: HashMap<&'static str, Vec<Mark>> = HashMap::new();
+    let mut map: HashMap<Arc<str>, Vec<Mark>> = HashMap::new();
     for mark in items {
-        map.entry(mark.language).or_default().push(mark);
+        map.entry(Arc::clone(&mark.language))
+            .or_default()
+            .push(mark);
     }
     let mut groups = map
         .into_iter()
         .map(|(key, items)| Group {
-            key: DimensionValue::Language(key.into()),
+            key: DimensionValue::Language(key.to_string().into()),
             items,
         })
         .collect::<Vec<_>>();
@@ -143,16 +150,118 @@ fn group_by_folder(items: Vec<Mark>, config: &FolderSortConfig, roots: &[PathBuf
     groups
 }
 
-fn mark_priority(mark: &str, overrides: &[MarkPriorityOverride]) -> Option<u8> {
-    for override_entry in overrides {
-        if override_entry.mark.eq_ignore_ascii_case(mark) {
-            return Some(override_entry.priority);
+fn group_by_owner(items: Vec<Mark>, config: &OwnerSortConfig) -> Vec<Group> {
+    group_by_values(
+        items,
+        config.order,
+        |mark| &mark.assignees,
+        |key| DimensionValue::Owner(key.into()),
+    )
+}
+
+fn group_by_issue(items: Vec<Mark>, config: &IssueSortConfig) -> Vec<Group> {
+    group_by_values(
+        items,
+        config.order,
+        |mark| &mark.issues,
+        |key| DimensionValue::Issue(key.into()),
+    )
+}
+
+fn group_by_due(items: Vec<Mark>, config: &DueSortConfig) -> Vec<Group> {
+    let mut map: HashMap<Option<DueDate>, Vec<Mark>> = HashMap::new();
+    for mark in items {
+        map.entry(mark.due).or_default().push(mark);
+    }
+    let mut entries = map.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|(a_key, _), (b_key, _)| match (a_key, b_key) {
+        (Some(a_due), Some(b_due)) => match config.order {
+            Order::Asc => a_due.cmp(b_due),
+            Order::Desc => b_due.cmp(a_due),
+        },
+        _ => a_key.is_none().cmp(&b_key.is_none()),
+    });
+    entries
+        .into_iter()
+        .map(|(key, items)| Group {
+            key: DimensionValue::Due(key),
+            items,
+        })
+        .collect()
+}
+
+fn group_by_comment(items: Vec<Mark>, config: &CommentSortConfig) -> Vec<Group> {
+    let mut map: HashMap<CommentKind, Vec<Mark>> = HashMap::new();
+    for mark in items {
+        map.entry(mark.comment_kind).or_default().push(mark);
+    }
+    let mut entries = map.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|(a_key, _), (b_key, _)| match config.order {
+        Order::Asc => a_key.cmp(b_key),
+        Order::Desc => b_key.cmp(a_key),
+    });
+    entries
+        .into_iter()
+        .map(|(key, items)| Group {
+            key: DimensionValue::Comment(key),
+            items,
+        })
+        .collect()
+}
+
+// A mark with several values appears under each of them. Marks without a
+// value share a group keyed by the empty string, placed last.
+fn group_by_values(
+    items: Vec<Mark>,
+    order: Order,
+    values_of: impl Fn(&Mark) -> &Vec<String>,
+    make_key: impl Fn(String) -> DimensionValue,
+) -> Vec<Group> {
+    let mut map: HashMap<String, Vec<Mark>> = HashMap::new();
+    for mark in items {
+        let mut keys = Vec::<String>::new();
+        for value in values_of(&mark) {
+            if !keys.iter().any(|key| key.eq_ignore_ascii_case(value)) {
+                keys.push(value.clone());
+            }
+        }
+        let last = keys.pop().unwrap_or_default();
+        for key in keys {
+            map.entry(key).or_default().push(mark.clone());
         }
+        map.entry(last).or_default().push(mark);
     }
-    for entry in DEFAULT_MARK_PRIORITIES {
-        if entry.mark == mark {
-            return Some(entry.priority);
+    let mut entries = map.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|(a_key, _), (b_key, _)| {
+        a_key
+            .is_empty()
+            .cmp(&b_key.is_empty())
+            .then_with(|| match order {
+                Order::Asc => a_key.cmp(b_key),
+                Order::Desc => b_key.cmp(a_key),
+            })
+    });
+    entries
+        .into_iter()
+        .map(|(key, items)| Group {
+            key: make_key(key),
+            items,
+        })
+        .collect()
+}
+
+fn mark_priority(
+    mark: &str,
+    overrides: &[MarkPriorityOverride],
+    marks: &MarkRegistry,
+) -> Option<u8> {
+    for override_entry in overrides {
+        let name = marks
+            .normalize(&override_entry.mark)
+            .map_or(override_entry.mark.as_str(), |name| name.as_ref());
+        if name.eq_ignore_ascii_case(mark) {
+            return Some(override_entry.priority);
         }
     }
-    None
+    marks.priority(mark)
 }
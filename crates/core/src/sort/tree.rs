@@ -1,10 +1,16 @@
 use std::path::PathBuf;
 
+use crate::marks::MarkRegistry;
 use crate::model::{GroupNode, GroupTree, Mark};
 use crate::sort::config::{DimensionStage, SortConfig};
 use crate::sort::stages::group_for_stage;
 
-pub fn build_group_tree(marks: Vec<Mark>, config: &SortConfig, roots: &[PathBuf]) -> GroupTree {
+pub fn build_group_tree(
+    marks: Vec<Mark>,
+    config: &SortConfig,
+    roots: &[PathBuf],
+    registry: &MarkRegistry,
+) -> GroupTree {
     if marks.is_empty() || config.pipeline.is_empty() {
         return GroupTree {
             groups: Vec::new(),
@@ -12,20 +18,25 @@ pub fn build_group_tree(marks: Vec<Mark>, config: &SortConfig, roots: &[PathBuf]
         };
     }
 
-    let groups = build_groups(&config.pipeline, roots, marks);
+    let groups = build_groups(&config.pipeline, roots, registry, marks);
     GroupTree {
         groups,
         items: Vec::new(),
     }
 }
 
-fn build_groups(stages: &[DimensionStage], roots: &[PathBuf], items: Vec<Mark>) -> Vec<GroupNode> {
+fn build_groups(
+    stages: &[DimensionStage],
+    roots: &[PathBuf],
+    registry: &MarkRegistry,
+    items: Vec<Mark>,
+) -> Vec<GroupNode> {
     if stages.is_empty() {
         return Vec::new();
     }
 
     let stage = &stages[0];
-    let groups = group_for_stage(stage, items, roots);
+    let groups = group_for_stage(stage, items, roots, registry);
     let mut out = Vec::with_capacity(groups.len());
     let next_stages = &stages[1..];
 
@@ -41,7 +52,7 @@ fn build_groups(stages: &[DimensionStage], roots: &[PathBuf], items: Vec<Mark>)
             continue;
         }
 
-        let children = build_groups(next_stages, roots, group.items);
+        let children = build_groups(next_stages, roots, registry, group.items);
         out.push(GroupNode {
             key: group.key,
             count,
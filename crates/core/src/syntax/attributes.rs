@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+const LANGUAGE_ATTR: &str = "linguist-language";
+const GENERATED_ATTR: &str = "linguist-generated";
+const VENDORED_ATTR: &str = "linguist-vendored";
+
+// Linguist overrides (`linguist-language`, `linguist-generated`,
+// `linguist-vendored`) read from `.gitattributes` files: those of the scan
+// root and its parent directories up to the repository top, loaded up front,
+// and those of directories below the root, loaded as files in them are looked
+// up.
+#[derive(Debug, Default)]
+pub(crate) struct GitAttributes {
+    root: PathBuf,
+    rules: Vec<AttributeRule>,
+    // Rules of each directory below the root, keyed by its path as walked.
+    nested: RwLock<HashMap<PathBuf, Arc<[AttributeRule]>>>,
+}
+
+#[derive(Debug)]
+struct AttributeRule {
+    // Location of the scan root relative to the `.gitattributes` file; empty
+    // for files below the root, whose rules match paths relative to their own
+    // directory.
+    prefix: PathBuf,
+    matcher: GlobMatcher,
+    file_name_only: bool,
+    // Outer `None` when the line does not mention the attribute; inner `None`
+    // when it unsets it (`-linguist-language`).
+    language: Option<Option<String>>,
+    generated: Option<bool>,
+    vendored: Option<bool>,
+}
+
+// State of one attribute on a `.gitattributes` line.
+enum AttrState<'a> {
+    Set,
+    Unset,
+    Value(&'a str),
+}
+
+impl GitAttributes {
+    pub(crate) fn load(root: &Path) -> Self {
+        let mut attributes = Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        };
+        let Ok(start) = fs::canonicalize(root) else {
+            return attributes;
+        };
+        let start = if start.is_file() {
+            attributes.root = root.parent().map(Path::to_path_buf).unwrap_or_default();
+            start.parent().map(Path::to_path_buf).unwrap_or(start)
+        } else {
+            start
+        };
+        let mut dirs = Vec::new();
+        for dir in start.ancestors() {
+            dirs.push(dir);
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        if !dirs.last().is_some_and(|dir| dir.join(".git").exists()) {
+            dirs.truncate(1);
+        }
+        for dir in dirs.into_iter().rev() {
+            let prefix = start.strip_prefix(dir).unwrap_or(Path::new(""));
+            attributes.rules.extend(read_rules(dir, prefix));
+        }
+        attributes
+    }
+
+    // Later lines, and files closer to the path, win.
+    pub(crate) fn language_for(&self, path: &Path) -> Option<String> {
+        self.find(path, |rule| rule.language.clone()).flatten()
+    }
+
+    // Generated or vendored code, which is not the project's to fix.
+    pub(crate) fn is_generated(&self, path: &Path) -> bool {
+        self.find(path, |rule| rule.generated).unwrap_or(false)
+            || self.find(path, |rule| rule.vendored).unwrap_or(false)
+    }
+
+    // Value of the last matching rule that mentions an attribute, looking at
+    // the path's own directory first and the root's ancestors last.
+    fn find<T>(&self, path: &Path, value: impl Fn(&AttributeRule) -> Option<T>) -> Option<T> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let name = path.file_name()?;
+        let mut dir = path.parent();
+        while let Some(current) = dir.filter(|dir| dir.starts_with(&self.root) && *dir != self.root)
+        {
+            let rules = self.nested_rules(current);
+            let inner = path.strip_prefix(current).unwrap_or(relative);
+            if let Some(found) = find_rule(&rules, inner, name, &value) {
+                return Some(found);
+            }
+            dir = current.parent();
+        }
+        find_rule(&self.rules, relative, name, &value)
+    }
+
+    fn nested_rules(&self, dir: &Path) -> Arc<[AttributeRule]> {
+        let cached = self
+            .nested
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(dir)
+            .cloned();
+        if let Some(rules) = cached {
+            return rules;
+        }
+        let rules: Arc<[AttributeRule]> = read_rules(dir, Path::new("")).into();
+        let mut nested = self.nested.write().unwrap_or_else(|err| err.into_inner());
+        Arc::clone(nested.entry(dir.to_path_buf()).or_insert(rules))
+    }
+}
+
+fn read_rules(dir: &Path, prefix: &Path) -> Vec<AttributeRule> {
+    let Ok(contents) = fs::read_to_string(dir.join(".gitattributes")) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| AttributeRule::parse(line, prefix))
+        .collect()
+}
+
+fn find_rule<T>(
+    rules: &[AttributeRule],
+    relative: &Path,
+    name: &OsStr,
+    value: &impl Fn(&AttributeRule) -> Option<T>,
+) -> Option<T> {
+    rules.iter().rev().find_map(|rule| {
+        let matched = if rule.file_name_only {
+            rule.matcher.is_match(name)
+        } else {
+            rule.matcher.is_match(rule.prefix.join(relative))
+        };
+        if matched { value(rule) } else { None }
+    })
+}
+
+impl AttributeRule {
+    fn parse(line: &str, prefix: &Path) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let pattern = fields.next()?;
+        // Quoted patterns and macro definitions are left alone.
+        if pattern.starts_with(['#', '"', '[']) || pattern.ends_with('/') {
+            return None;
+        }
+        let attrs = fields.collect::<Vec<_>>();
+        let language = attr_state(&attrs, LANGUAGE_ATTR).and_then(|state| match state {
+            AttrState::Set => None,
+            AttrState::Unset => Some(None),
+            AttrState::Value(value) => Some(Some(value.to_string())),
+        });
+        let generated = attr_state(&attrs, GENERATED_ATTR).map(AttrState::into_bool);
+        let vendored = attr_state(&attrs, VENDORED_ATTR).map(AttrState::into_bool);
+        if language.is_none() && generated.is_none() && vendored.is_none() {
+            return None;
+        }
+        let file_name_only = !pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .ok()?;
+        Some(Self {
+            prefix: prefix.to_path_buf(),
+            matcher: glob.compile_matcher(),
+            file_name_only,
+            language,
+            generated,
+            vendored,
+        })
+    }
+}
+
+impl AttrState<'_> {
+    fn into_bool(self) -> bool {
+        match self {
+            AttrState::Set => true,
+            AttrState::Unset => false,
+            AttrState::Value(value) => value != "false",
+        }
+    }
+}
+
+// The last mention of `name` wins: `name`, `name=value`, or `-name`/`!name`
+// to unset it.
+fn attr_state<'a>(attrs: &[&'a str], name: &str) -> Option<AttrState<'a>> {
+    attrs.iter().rev().find_map(|attr| {
+        if let Some(rest) = attr.strip_prefix(name) {
+            return match rest.strip_prefix('=') {
+                Some(value) => Some(AttrState::Value(value)),
+                None => rest.is_empty().then_some(AttrState::Set),
+            };
+        }
+        let unset = attr.strip_prefix(['-', '!'])?;
+        (unset == name).then_some(AttrState::Unset)
+    })
+}
@@ -2,24 +2,143 @@ use std::path::Path;
 
 use memchr::{memchr, memchr2, memchr3};
 
+use crate::model::CommentKind;
+use crate::syntax::detect::syntax_for_language;
+use crate::syntax::language::Language;
+use crate::syntax::mapping::LanguageMap;
+
 #[derive(Clone, Copy, Debug)]
-pub struct StringDelim {
-    pub token: &'static [u8],
+pub struct StringDelim<'a> {
+    pub token: &'a [u8],
     pub multiline: bool,
     pub escape: bool,
+    // Letters that may prefix the opening token, such as Python's `r`, `b`,
+    // `f` and `u`.
+    pub prefixes: &'a [u8],
+    pub interpolation: Option<Interpolation<'a>>,
+}
+
+// Code embedded in a string literal, such as `{name}` in a Python f-string.
+#[derive(Clone, Copy, Debug)]
+pub struct Interpolation<'a> {
+    pub open: &'a [u8],
+    // Prefix letter that enables interpolation (`f`); `None` when every
+    // literal of the kind interpolates.
+    pub prefix: Option<u8>,
+    // A top-level `:` starts a format spec, which reads as string text again.
+    pub format_spec: bool,
+}
+
+// A comment block whose delimiters must start a line, such as Ruby's
+// `=begin`/`=end` or Perl's POD.
+#[derive(Clone, Copy, Debug)]
+pub struct LineBlock<'a> {
+    pub open: &'a [&'a [u8]],
+    pub close: &'a [u8],
+    pub kind: CommentKind,
+}
+
+// Literal forms whose end is not a fixed closing token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RawString {
+    // Rust `r#"..."#`: the closing quote repeats the opening hashes.
+    Hashed,
+    // C++ `R"delim(...)delim"`.
+    Parenthesized,
+    // C# `@"..."`: no escapes, and `""` stands for a quote.
+    Verbatim,
+    // C# `"""..."""`: three or more quotes, closed by as many.
+    QuoteRun,
+    // `<<EOF` up to a line holding only `EOF`. Shells also accept `<< EOF`.
+    Heredoc { spaced: bool },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SyntaxSpec<'a> {
+    pub line_comment: Option<&'a [u8]>,
+    // A second line comment token, such as MySQL's `#` next to `--`.
+    pub alt_line_comment: Option<&'a [u8]>,
+    pub block_comment: Option<(&'a [u8], &'a [u8])>,
+    pub strings: &'a [StringDelim<'a>],
+    pub raw_strings: &'a [RawString],
+    pub doc_line: &'a [&'a [u8]],
+    pub doc_block: &'a [&'a [u8]],
+    pub docstrings: bool,
+    pub nested_blocks: bool,
+    pub long_brackets: bool,
+    pub dollar_quotes: bool,
+    // Tracks C preprocessor conditionals so `#if 0` blocks are known to be
+    // dead code.
+    pub preprocessor: bool,
+    // JavaScript `/.../` regex literals, told apart from division by the
+    // token before the slash.
+    pub regex_literals: bool,
+    // `'x'` character literals in languages where a lone `'` is also part of
+    // names (`x'`) or type variables (`'a`), so it cannot open a string.
+    pub char_literals: bool,
+    pub line_block: Option<LineBlock<'a>>,
+    // Words allowed before a docstring opener, such as Elixir's `@doc`. When
+    // set, a bare opener is an ordinary string.
+    pub docstring_prefixes: &'a [&'a [u8]],
+    pub markup: bool,
+    pub regions: &'a [EmbeddedRegion],
+}
+
+impl<'a> SyntaxSpec<'a> {
+    // The line comment token that starts at `idx`, if any.
+    pub(crate) fn line_comment_at(&self, line: &[u8], idx: usize) -> Option<&'a [u8]> {
+        self.line_comment
+            .into_iter()
+            .chain(self.alt_line_comment)
+            .find(|token| starts_with(line, token, idx))
+    }
+}
+
+// A region of a markup file written in another language.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedRegion {
+    pub open: RegionOpen,
+    pub spec: &'static SyntaxSpec<'static>,
+    pub language: &'static str,
+    pub variants: &'static [RegionVariant],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RegionOpen {
+    // `<name ...>` up to `</name`.
+    Tag(&'static [u8]),
+    // An opening token up to a closing token, such as `<?php` and `?>`.
+    Delimited(&'static [u8], &'static [u8]),
+    // A fence on the first line up to the next identical fence line.
+    Frontmatter(&'static [u8]),
+    // A Markdown code fence (```` ``` ```` or `~~~`) whose info string names
+    // the language, up to the closing fence.
+    CodeFence,
+}
+
+// Selected by the `lang` attribute of a tag region.
+#[derive(Clone, Copy, Debug)]
+pub struct RegionVariant {
+    pub lang: &'static [u8],
+    pub spec: &'static SyntaxSpec<'static>,
+    pub language: &'static str,
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct SyntaxSpec {
-    pub line_comment: Option<&'static [u8]>,
-    pub block_comment: Option<(&'static [u8], &'static [u8])>,
-    pub strings: &'static [StringDelim],
-    pub raw_string: bool,
+pub struct CommentRange<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub kind: CommentKind,
+    pub spec: &'a SyntaxSpec<'a>,
+    // Set when the comment sits in an embedded region.
+    pub language: Option<&'static str>,
+    // Set when the comment sits in code disabled by the preprocessor.
+    pub disabled: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct SyntaxInfo {
-    pub spec: &'static SyntaxSpec,
+    pub spec: &'static SyntaxSpec<'static>,
     pub language: &'static str,
 }
 
@@ -28,29 +147,93 @@ const C_STYLE_STRINGS: &[StringDelim] = &[
         token: b"\"",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"'",
+        multiline: false,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const TEXT_BLOCK_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"\"\"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: false,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"'",
+        multiline: false,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const RAW_TEXT_BLOCK_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"\"\"\"",
+        multiline: true,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: false,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
 ];
 
+const TEMPLATE: Interpolation = Interpolation {
+    open: b"${",
+    prefix: None,
+    format_spec: false,
+};
+
 const C_STYLE_JS_STRINGS: &[StringDelim] = &[
     StringDelim {
         token: b"`",
         multiline: true,
         escape: true,
+        prefixes: &[],
+        interpolation: Some(TEMPLATE),
     },
     StringDelim {
         token: b"\"",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
 ];
 
@@ -59,34 +242,54 @@ const HASH_STRINGS: &[StringDelim] = &[
         token: b"\"",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
 ];
 
+const PY_PREFIXES: &[u8] = b"rbfu";
+
+const F_STRING: Interpolation = Interpolation {
+    open: b"{",
+    prefix: Some(b'f'),
+    format_spec: true,
+};
+
 const PY_STRINGS: &[StringDelim] = &[
     StringDelim {
         token: b"\"\"\"",
         multiline: true,
         escape: true,
+        prefixes: PY_PREFIXES,
+        interpolation: Some(F_STRING),
     },
     StringDelim {
         token: b"'''",
         multiline: true,
         escape: true,
+        prefixes: PY_PREFIXES,
+        interpolation: Some(F_STRING),
     },
     StringDelim {
         token: b"\"",
         multiline: false,
         escape: true,
+        prefixes: PY_PREFIXES,
+        interpolation: Some(F_STRING),
     },
     StringDelim {
         token: b"'",
         multiline: false,
         escape: true,
+        prefixes: PY_PREFIXES,
+        interpolation: Some(F_STRING),
     },
 ];
 
@@ -95,21 +298,29 @@ const TOML_STRINGS: &[StringDelim] = &[
         token: b"\"\"\"",
         multiline: true,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'''",
         multiline: true,
         escape: false,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"\"",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'",
         multiline: false,
         escape: false,
+        prefixes: &[],
+        interpolation: None,
     },
 ];
 
@@ -118,61 +329,477 @@ const SHELL_STRINGS: &[StringDelim] = &[
         token: b"\"",
         multiline: true,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'",
         multiline: true,
         escape: false,
+        prefixes: &[],
+        interpolation: None,
     },
 ];
 
+const DOC_LINE_SLASHES: &[&[u8]] = &[b"///", b"//!"];
+
+const DOC_BLOCK_STARS: &[&[u8]] = &[b"/**", b"/*!"];
+
 const C_STYLE: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"//"),
+    alt_line_comment: None,
     block_comment: Some((b"/*", b"*/")),
     strings: C_STYLE_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: DOC_LINE_SLASHES,
+    doc_block: DOC_BLOCK_STARS,
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const C_PREPROCESSED: SyntaxSpec = SyntaxSpec {
+    preprocessor: true,
+    ..C_STYLE
+};
+
+const CPP: SyntaxSpec = SyntaxSpec {
+    raw_strings: &[RawString::Parenthesized],
+    ..C_PREPROCESSED
+};
+
+const CSHARP: SyntaxSpec = SyntaxSpec {
+    raw_strings: &[RawString::QuoteRun, RawString::Verbatim],
+    ..C_STYLE
+};
+
+const JAVA: SyntaxSpec = SyntaxSpec {
+    strings: TEXT_BLOCK_STRINGS,
+    ..C_STYLE
+};
+
+// `#error` and `#warning` text, where a lone `'` is not a character literal.
+const C_DIAGNOSTIC: SyntaxSpec = SyntaxSpec {
+    strings: DOUBLE_QUOTE_STRINGS,
+    ..C_PREPROCESSED
+};
+
+const C_STYLE_NESTED: SyntaxSpec = SyntaxSpec {
+    nested_blocks: true,
+    ..C_STYLE
+};
+
+const SWIFT: SyntaxSpec = SyntaxSpec {
+    strings: TEXT_BLOCK_STRINGS,
+    ..C_STYLE_NESTED
+};
+
+// Kotlin and Scala `"""` strings are raw.
+const KOTLIN: SyntaxSpec = SyntaxSpec {
+    strings: RAW_TEXT_BLOCK_STRINGS,
+    ..C_STYLE_NESTED
 };
 
 const C_STYLE_JS: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"//"),
+    alt_line_comment: None,
     block_comment: Some((b"/*", b"*/")),
     strings: C_STYLE_JS_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[b"/**"],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: true,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
 };
 
 const HASH_SIMPLE: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"#"),
+    alt_line_comment: None,
     block_comment: None,
     strings: HASH_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
 };
 
 const HASH_PY: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"#"),
+    alt_line_comment: None,
     block_comment: None,
     strings: PY_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: true,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const RUBY: SyntaxSpec = SyntaxSpec {
+    raw_strings: &[RawString::Heredoc { spaced: false }],
+    line_block: Some(LineBlock {
+        open: &[b"=begin"],
+        close: b"=end",
+        kind: CommentKind::Block,
+    }),
+    ..HASH_SIMPLE
+};
+
+const MULTILINE_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"'",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+// Strings stay on one line so a quote inside a regex (`s/"//g`) cannot hide
+// the lines after it.
+const PERL: SyntaxSpec = SyntaxSpec {
+    raw_strings: &[RawString::Heredoc { spaced: false }],
+    line_block: Some(LineBlock {
+        open: &[
+            b"=pod",
+            b"=head",
+            b"=over",
+            b"=item",
+            b"=begin",
+            b"=for",
+            b"=encoding",
+        ],
+        close: b"=cut",
+        kind: CommentKind::DocBlock,
+    }),
+    ..HASH_SIMPLE
+};
+
+const R: SyntaxSpec = SyntaxSpec {
+    strings: MULTILINE_STRINGS,
+    ..HASH_SIMPLE
+};
+
+const ELIXIR_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"\"\"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"'''",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"'",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const ELIXIR: SyntaxSpec = SyntaxSpec {
+    strings: ELIXIR_STRINGS,
+    docstrings: true,
+    docstring_prefixes: &[b"@doc", b"@moduledoc", b"@typedoc"],
+    ..HASH_SIMPLE
+};
+
+const ERLANG: SyntaxSpec = SyntaxSpec {
+    line_comment: Some(b"%"),
+    strings: MULTILINE_STRINGS,
+    ..HASH_SIMPLE
+};
+
+const JULIA_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"\"\"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const JULIA: SyntaxSpec = SyntaxSpec {
+    block_comment: Some((b"#=", b"=#")),
+    strings: JULIA_STRINGS,
+    nested_blocks: true,
+    ..HASH_SIMPLE
+};
+
+// Backticks, not backslashes, escape in PowerShell strings.
+const POWERSHELL_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"\"",
+        multiline: true,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"'",
+        multiline: true,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const POWERSHELL: SyntaxSpec = SyntaxSpec {
+    block_comment: Some((b"<#", b"#>")),
+    strings: POWERSHELL_STRINGS,
+    ..HASH_SIMPLE
+};
+
+// `'` quotes forms in Lisps, so only `"` opens a string.
+const LISP_STRINGS: &[StringDelim] = &[StringDelim {
+    token: b"\"",
+    multiline: true,
+    escape: true,
+    prefixes: &[],
+    interpolation: None,
+}];
+
+const CLOJURE: SyntaxSpec = SyntaxSpec {
+    line_comment: Some(b";"),
+    strings: LISP_STRINGS,
+    ..HASH_SIMPLE
+};
+
+const LISP: SyntaxSpec = SyntaxSpec {
+    block_comment: Some((b"#|", b"|#")),
+    nested_blocks: true,
+    ..CLOJURE
+};
+
+// A `"` opens a string or starts a comment; see `quote_starts_comment`.
+const VIM_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"'",
+        multiline: false,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: false,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const VIM: SyntaxSpec = SyntaxSpec {
+    line_comment: Some(b"\""),
+    strings: VIM_STRINGS,
+    ..HASH_SIMPLE
 };
 
 const HASH_TOML: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"#"),
+    alt_line_comment: None,
     block_comment: None,
     strings: TOML_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
 };
 
 const HASH_SHELL: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"#"),
+    alt_line_comment: None,
     block_comment: None,
     strings: SHELL_STRINGS,
-    raw_string: false,
+    raw_strings: &[RawString::Heredoc { spaced: true }],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
 };
 
 const LUA: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"--"),
+    alt_line_comment: None,
     block_comment: None,
     strings: HASH_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: true,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const SQL_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"'",
+        multiline: true,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: true,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+const MYSQL_STRINGS: &[StringDelim] = &[
+    StringDelim {
+        token: b"'",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"\"",
+        multiline: true,
+        escape: true,
+        prefixes: &[],
+        interpolation: None,
+    },
+    StringDelim {
+        token: b"`",
+        multiline: true,
+        escape: false,
+        prefixes: &[],
+        interpolation: None,
+    },
+];
+
+// `''` inside a literal closes and reopens the string, so it needs no escape
+// handling.
+// A `$tag$` quote cannot be mistaken for anything else, so plain `.sql` files
+// accept PostgreSQL's dollar quoting too.
+const SQL: SyntaxSpec = SyntaxSpec {
+    line_comment: Some(b"--"),
+    alt_line_comment: None,
+    block_comment: Some((b"/*", b"*/")),
+    strings: SQL_STRINGS,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: true,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const POSTGRES: SyntaxSpec = SyntaxSpec {
+    nested_blocks: true,
+    ..SQL
+};
+
+// `DELIMITER $$` would read as a dollar quote.
+const MYSQL: SyntaxSpec = SyntaxSpec {
+    alt_line_comment: Some(b"#"),
+    dollar_quotes: false,
+    strings: MYSQL_STRINGS,
+    ..SQL
 };
 
 const GO_STRINGS: &[StringDelim] = &[
@@ -180,162 +807,705 @@ const GO_STRINGS: &[StringDelim] = &[
         token: b"`",
         multiline: true,
         escape: false,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"\"",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
     StringDelim {
         token: b"'",
         multiline: false,
         escape: true,
+        prefixes: &[],
+        interpolation: None,
     },
 ];
 
 const GO: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"//"),
+    alt_line_comment: None,
     block_comment: Some((b"/*", b"*/")),
     strings: GO_STRINGS,
-    raw_string: false,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
 };
 
 const RUST: SyntaxSpec = SyntaxSpec {
     line_comment: Some(b"//"),
+    alt_line_comment: None,
     block_comment: Some((b"/*", b"*/")),
     strings: C_STYLE_STRINGS,
-    raw_string: true,
+    raw_strings: &[RawString::Hashed],
+    doc_line: DOC_LINE_SLASHES,
+    doc_block: DOC_BLOCK_STARS,
+    docstrings: false,
+    nested_blocks: true,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
 };
 
-pub fn syntax_for_path(path: &Path) -> Option<SyntaxInfo> {
-    let ext = path
-        .extension()
-        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
-    let name = path
-        .file_name()
-        .map(|name| name.to_string_lossy().to_ascii_lowercase());
-    if let Some(name) = name.as_deref() {
-        if name == "makefile" {
-            return Some(SyntaxInfo {
-                spec: &HASH_SIMPLE,
-                language: "makefile",
-            });
-        }
-        if name == "dockerfile" {
-            return Some(SyntaxInfo {
-                spec: &HASH_SIMPLE,
-                language: "dockerfile",
-            });
-        }
-    }
-    let ext = ext.as_deref()?;
-    match ext {
-        "rs" => Some(SyntaxInfo {
-            spec: &RUST,
-            language: "rs",
-        }),
-        "c" | "h" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "c",
-        }),
-        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "cpp",
-        }),
-        "java" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "java",
-        }),
-        "kt" | "kts" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "kotlin",
-        }),
-        "swift" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "swift",
-        }),
-        "go" => Some(SyntaxInfo {
-            spec: &GO,
-            language: "go",
-        }),
-        "cs" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "cs",
-        }),
-        "scala" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "scala",
-        }),
-        "dart" => Some(SyntaxInfo {
-            spec: &C_STYLE,
-            language: "dart",
-        }),
-        "js" | "jsx" => Some(SyntaxInfo {
-            spec: &C_STYLE_JS,
-            language: "js",
-        }),
-        "ts" | "tsx" => Some(SyntaxInfo {
-            spec: &C_STYLE_JS,
-            language: "ts",
-        }),
-        "py" => Some(SyntaxInfo {
-            spec: &HASH_PY,
-            language: "py",
-        }),
-        "sh" | "bash" | "zsh" => Some(SyntaxInfo {
-            spec: &HASH_SHELL,
-            language: "sh",
-        }),
-        "toml" => Some(SyntaxInfo {
-            spec: &HASH_TOML,
-            language: "toml",
-        }),
-        "rb" => Some(SyntaxInfo {
-            spec: &HASH_SIMPLE,
-            language: "rb",
-        }),
-        "yml" | "yaml" => Some(SyntaxInfo {
-            spec: &HASH_SIMPLE,
-            language: "yaml",
-        }),
-        "ini" | "cfg" | "conf" | "env" => Some(SyntaxInfo {
-            spec: &HASH_SIMPLE,
-            language: "ini",
-        }),
-        "lua" => Some(SyntaxInfo {
-            spec: &LUA,
-            language: "lua",
-        }),
-        "mk" => Some(SyntaxInfo {
-            spec: &HASH_SIMPLE,
-            language: "make",
-        }),
-        _ => None,
-    }
-}
+const DOUBLE_QUOTE_STRINGS: &[StringDelim] = &[StringDelim {
+    token: b"\"",
+    multiline: false,
+    escape: true,
+    prefixes: &[],
+    interpolation: None,
+}];
 
-#[derive(Clone, Copy, Debug, Default)]
-pub struct BlockState {
-    pub in_block: bool,
-    in_string: Option<usize>,
+const HASKELL: SyntaxSpec = SyntaxSpec {
+    line_comment: Some(b"--"),
+    alt_line_comment: None,
+    block_comment: Some((b"{-", b"-}")),
+    strings: DOUBLE_QUOTE_STRINGS,
+    raw_strings: &[],
+    doc_line: &[b"-- |", b"-- ^"],
+    doc_block: &[b"{-|"],
+    docstrings: false,
+    nested_blocks: true,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: true,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const OCAML: SyntaxSpec = SyntaxSpec {
+    line_comment: None,
+    alt_line_comment: None,
+    block_comment: Some((b"(*", b"*)")),
+    strings: DOUBLE_QUOTE_STRINGS,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[b"(**"],
+    docstrings: false,
+    nested_blocks: true,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: true,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const CSS: SyntaxSpec = SyntaxSpec {
+    line_comment: None,
+    alt_line_comment: None,
+    block_comment: Some((b"/*", b"*/")),
+    strings: C_STYLE_STRINGS,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const PHP: SyntaxSpec = SyntaxSpec {
+    line_comment: Some(b"//"),
+    alt_line_comment: None,
+    block_comment: Some((b"/*", b"*/")),
+    strings: C_STYLE_STRINGS,
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[b"/**"],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const SCRIPT_VARIANTS: &[RegionVariant] = &[
+    RegionVariant {
+        lang: b"ts",
+        spec: &C_STYLE_JS,
+        language: "typescript",
+    },
+    RegionVariant {
+        lang: b"typescript",
+        spec: &C_STYLE_JS,
+        language: "typescript",
+    },
+];
+
+const STYLE_VARIANTS: &[RegionVariant] = &[
+    RegionVariant {
+        lang: b"scss",
+        spec: &C_STYLE,
+        language: "scss",
+    },
+    RegionVariant {
+        lang: b"less",
+        spec: &C_STYLE,
+        language: "less",
+    },
+];
+
+const SCRIPT_REGION: EmbeddedRegion = EmbeddedRegion {
+    open: RegionOpen::Tag(b"script"),
+    spec: &C_STYLE_JS,
+    language: "javascript",
+    variants: SCRIPT_VARIANTS,
+};
+
+const STYLE_REGION: EmbeddedRegion = EmbeddedRegion {
+    open: RegionOpen::Tag(b"style"),
+    spec: &CSS,
+    language: "css",
+    variants: STYLE_VARIANTS,
+};
+
+const MARKUP: SyntaxSpec = SyntaxSpec {
+    line_comment: None,
+    alt_line_comment: None,
+    block_comment: Some((b"<!--", b"-->")),
+    strings: &[],
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: true,
+    regions: &[],
+};
+
+const HTML: SyntaxSpec = SyntaxSpec {
+    regions: &[SCRIPT_REGION, STYLE_REGION],
+    ..MARKUP
+};
+
+const ASTRO: SyntaxSpec = SyntaxSpec {
+    regions: &[
+        EmbeddedRegion {
+            open: RegionOpen::Frontmatter(b"---"),
+            spec: &C_STYLE_JS,
+            language: "typescript",
+            variants: &[],
+        },
+        SCRIPT_REGION,
+        STYLE_REGION,
+    ],
+    ..MARKUP
+};
+
+const PHP_HOST: SyntaxSpec = SyntaxSpec {
+    regions: &[
+        EmbeddedRegion {
+            open: RegionOpen::Delimited(b"<?php", b"?>"),
+            spec: &PHP,
+            language: "php",
+            variants: &[],
+        },
+        EmbeddedRegion {
+            open: RegionOpen::Delimited(b"<?=", b"?>"),
+            spec: &PHP,
+            language: "php",
+            variants: &[],
+        },
+        SCRIPT_REGION,
+        STYLE_REGION,
+    ],
+    ..MARKUP
+};
+
+// Text with no comment syntax, such as a code block of an unknown language.
+const PLAIN: SyntaxSpec = SyntaxSpec {
+    line_comment: None,
+    alt_line_comment: None,
+    block_comment: None,
+    strings: &[],
+    raw_strings: &[],
+    doc_line: &[],
+    doc_block: &[],
+    docstrings: false,
+    nested_blocks: false,
+    long_brackets: false,
+    dollar_quotes: false,
+    preprocessor: false,
+    regex_literals: false,
+    char_literals: false,
+    line_block: None,
+    docstring_prefixes: &[],
+    markup: false,
+    regions: &[],
+};
+
+const MARKDOWN: SyntaxSpec = SyntaxSpec {
+    regions: &[
+        EmbeddedRegion {
+            open: RegionOpen::Frontmatter(b"---"),
+            spec: &HASH_SIMPLE,
+            language: "yaml",
+            variants: &[],
+        },
+        EmbeddedRegion {
+            open: RegionOpen::Frontmatter(b"+++"),
+            spec: &HASH_TOML,
+            language: "toml",
+            variants: &[],
+        },
+        EmbeddedRegion {
+            open: RegionOpen::CodeFence,
+            spec: &PLAIN,
+            language: "text",
+            variants: &[],
+        },
+    ],
+    ..MARKUP
+};
+
+// Language mappings are applied before the built-in table.
+pub fn syntax_for_path(path: &Path, mappings: &LanguageMap) -> Option<SyntaxInfo> {
+    if let Some(info) = mappings.language_for(path).and_then(syntax_for_language) {
+        return Some(info);
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase());
+    if let Some(info) = name.as_deref().and_then(syntax_for_file_name) {
+        return Some(info);
+    }
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    syntax_for_extension(ext.as_deref()?)
+}
+
+// Well-known files that carry no (or a misleading) extension.
+pub(crate) fn syntax_for_file_name(name: &str) -> Option<SyntaxInfo> {
+    match name {
+        "makefile" | "gnumakefile" => syntax_for_extension("mk"),
+        "dockerfile" | "containerfile" => syntax_for_extension("dockerfile"),
+        "jenkinsfile" => syntax_for_extension("groovy"),
+        "vagrantfile" | "rakefile" | "gemfile" | "podfile" | "brewfile" | "guardfile"
+        | "fastfile" | "capfile" | "berksfile" => syntax_for_extension("rb"),
+        ".bashrc" | ".bash_profile" | ".bash_login" | ".bash_logout" | ".profile" | ".zshrc"
+        | ".zshenv" | ".zprofile" | ".zlogin" | "pkgbuild" => syntax_for_extension("sh"),
+        "cmakelists.txt" => syntax_for_extension("cmake"),
+        ".vimrc" | "_vimrc" | ".gvimrc" | "_gvimrc" => syntax_for_extension("vim"),
+        ".rprofile" => syntax_for_extension("r"),
+        ".emacs" => syntax_for_extension("el"),
+        _ if name.starts_with("dockerfile.") => syntax_for_file_name("dockerfile"),
+        _ => None,
+    }
+}
+
+// The built-in languages: canonical name, syntax, the file extensions that
+// select it (the first is its primary one) and other accepted spellings.
+pub(crate) const LANGUAGES: &[Language] = &[
+    Language::new("astro", &ASTRO, &["astro"], &[]),
+    Language::new("c", &C_PREPROCESSED, &["c", "h"], &[]),
+    Language::new("clojure", &CLOJURE, &["clj", "cljs", "cljc", "edn"], &[]),
+    Language::new("cmake", &HASH_SIMPLE, &["cmake"], &[]),
+    Language::new(
+        "cpp",
+        &CPP,
+        &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+        &["c++"],
+    ),
+    Language::new("csharp", &CSHARP, &["cs"], &["c#"]),
+    Language::new("css", &CSS, &[], &[]),
+    Language::new("dart", &C_STYLE, &["dart"], &[]),
+    Language::new(
+        "dockerfile",
+        &HASH_SIMPLE,
+        &["dockerfile"],
+        &["docker", "containerfile"],
+    ),
+    Language::new("elixir", &ELIXIR, &["ex", "exs"], &[]),
+    Language::new("erlang", &ERLANG, &["erl", "hrl"], &["escript"]),
+    Language::new("go", &GO, &["go"], &["golang"]),
+    Language::new("groovy", &C_STYLE, &["groovy", "gradle"], &[]),
+    Language::new("haskell", &HASKELL, &["hs"], &[]),
+    Language::new("html", &HTML, &["html", "htm", "xhtml"], &[]),
+    Language::new(
+        "ini",
+        &HASH_SIMPLE,
+        &["ini", "cfg", "conf", "env"],
+        &["dosini"],
+    ),
+    Language::new("java", &JAVA, &["java"], &[]),
+    Language::new("javascript", &C_STYLE_JS, &["js", "jsx"], &["node"]),
+    Language::new("julia", &JULIA, &["jl"], &[]),
+    Language::new("kotlin", &KOTLIN, &["kt", "kts"], &[]),
+    Language::new("less", &C_STYLE, &[], &[]),
+    Language::new(
+        "lisp",
+        &LISP,
+        &["lisp", "lsp", "cl", "el"],
+        &["common-lisp", "elisp", "emacs-lisp"],
+    ),
+    Language::new("lua", &LUA, &["lua"], &[]),
+    Language::new("makefile", &HASH_SIMPLE, &["mk"], &["make"]),
+    Language::new("markdown", &MARKDOWN, &["md", "markdown"], &[]),
+    Language::new("mysql", &MYSQL, &["mysql"], &[]),
+    Language::new("ocaml", &OCAML, &["ml", "mli"], &[]),
+    Language::new("perl", &PERL, &["pl", "pm"], &["cperl"]),
+    Language::new("php", &PHP_HOST, &["php", "phtml"], &[]),
+    Language::new("postgresql", &POSTGRES, &["pgsql", "psql"], &["postgres"]),
+    Language::new(
+        "powershell",
+        &POWERSHELL,
+        &["ps1", "psm1", "psd1"],
+        &["pwsh", "posh"],
+    ),
+    Language::new("python", &HASH_PY, &["py"], &[]),
+    Language::new("r", &R, &["r"], &["rscript"]),
+    Language::new("ruby", &RUBY, &["rb"], &[]),
+    Language::new("rust", &RUST, &["rs"], &[]),
+    Language::new("scala", &KOTLIN, &["scala"], &[]),
+    Language::new("scheme", &LISP, &["scm", "ss", "rkt"], &["racket", "guile"]),
+    Language::new("scss", &C_STYLE, &[], &[]),
+    Language::new("shell", &HASH_SHELL, &["sh", "bash", "zsh"], &[]),
+    Language::new("sql", &SQL, &["sql"], &[]),
+    Language::new("svelte", &HTML, &["svelte"], &[]),
+    Language::new("svg", &MARKUP, &["svg"], &[]),
+    Language::new("swift", &SWIFT, &["swift"], &[]),
+    Language::new("toml", &HASH_TOML, &["toml"], &[]),
+    Language::new("typescript", &C_STYLE_JS, &["ts", "tsx"], &[]),
+    Language::new("vim", &VIM, &["vim"], &["vimscript", "viml"]),
+    Language::new("vue", &HTML, &["vue"], &[]),
+    Language::new("xaml", &MARKUP, &["xaml"], &[]),
+    Language::new("xml", &MARKUP, &["xml", "xsd", "xsl", "xslt", "plist"], &[]),
+    Language::new("yaml", &HASH_SIMPLE, &["yml", "yaml"], &[]),
+];
+
+pub(crate) fn syntax_for_extension(ext: &str) -> Option<SyntaxInfo> {
+    Language::for_extension(ext).map(Language::syntax)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BlockState {
+    lex: LexState,
+    embedded: LexState,
+    region: Option<ActiveRegion>,
+    pending_region: Option<ActiveRegion>,
+    started: bool,
+    conditionals: Vec<Conditional>,
+}
+
+impl BlockState {
+    // Inside an embedded region, such as front matter or a code block.
+    pub fn in_region(&self) -> bool {
+        self.region.is_some()
+    }
+
+    // Inside a branch the preprocessor is known to drop, such as `#if 0`.
+    pub fn in_disabled_code(&self) -> bool {
+        self.conditionals.iter().any(|group| !group.active)
+    }
+
+    pub fn in_comment(&self) -> bool {
+        if self.region.is_some() {
+            self.embedded.in_comment()
+        } else {
+            self.lex.in_comment()
+        }
+    }
+}
+
+// One `#if` ... `#endif` group. `taken` records whether a branch is known to
+// have been selected; it is `None` once a condition cannot be evaluated.
+#[derive(Clone, Copy, Debug)]
+struct Conditional {
+    active: bool,
+    taken: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ActiveRegion {
+    open: RegionOpen,
+    spec: &'static SyntaxSpec<'static>,
+    language: &'static str,
+    // Character and length of an open code fence.
+    fence: Option<(u8, usize)>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct LexState {
+    in_block: bool,
+    block_depth: u32,
+    long_bracket: Option<LongBracket>,
+    dollar_tag: Option<Box<[u8]>>,
+    block_kind: Option<CommentKind>,
+    docstring: Option<usize>,
+    in_string: Option<usize>,
+    string_interpolates: bool,
     escape: bool,
-    raw_string_hash: Option<usize>,
+    interpolations: Vec<OpenInterpolation>,
+    raw_close: Option<RawClose>,
+    heredocs: Vec<HeredocTag>,
+    // The code before this line ended with an operand, so a leading `/` is
+    // division rather than a regex.
+    after_operand: bool,
+    // Python statement tracking for docstrings: whether any code has been
+    // seen, whether a `def`/`class` header is still open, whether the last
+    // statement opened a body, and whether this line may hold a docstring.
+    code_started: bool,
+    in_header: bool,
+    body_opened: bool,
+    docstring_allowed: bool,
+    in_line_block: bool,
+    in_tag: bool,
+    in_cdata: bool,
+    attr_quote: Option<u8>,
+}
+
+impl LexState {
+    fn in_comment(&self) -> bool {
+        self.in_block
+            || self.in_line_block
+            || self.docstring.is_some()
+            || self.long_bracket.is_some_and(|open| open.comment)
+    }
 }
 
-pub fn find_comment_ranges(
+// An interpolation whose code is being scanned, with the depth of brackets
+// opened inside it.
+#[derive(Clone, Copy, Debug)]
+struct OpenInterpolation {
+    string: usize,
+    depth: u32,
+}
+
+// Closing token of an open raw literal; `doubled` means a repeated first byte
+// is an escape rather than the end (`""` in C# verbatim strings).
+#[derive(Clone, Debug)]
+struct RawClose {
+    token: Box<[u8]>,
+    doubled: bool,
+}
+
+// A heredoc whose body starts on the next line. `indented` allows leading
+// whitespace before the closing tag (`<<-EOF`, `<<~EOF`).
+#[derive(Clone, Debug)]
+struct HeredocTag {
+    tag: Box<[u8]>,
+    indented: bool,
+}
+
+impl HeredocTag {
+    fn closes(&self, line: &[u8]) -> bool {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = if self.indented {
+            line.trim_ascii_start()
+        } else {
+            line
+        };
+        line == &*self.tag
+    }
+}
+
+enum RawOpen {
+    Close(RawClose),
+    Heredoc(HeredocTag),
+}
+
+// An open Lua long bracket (`[==[`) and whether it started a `--` comment.
+#[derive(Clone, Copy, Debug)]
+struct LongBracket {
+    level: usize,
+    comment: bool,
+}
+
+pub fn find_comment_ranges<'a>(
     line: &[u8],
     state: &mut BlockState,
-    spec: &SyntaxSpec,
-    mut on_range: impl FnMut(usize, usize),
+    spec: &'a SyntaxSpec<'a>,
+    mut on_range: impl FnMut(CommentRange<'a>),
 ) {
-    find_ranges(line, state, spec, &mut on_range);
+    if spec.markup {
+        find_markup_ranges(line, state, spec, &mut on_range);
+        return;
+    }
+    let mut disabled = false;
+    let mut from = 0;
+    let mut lex_spec = spec;
+    if spec.preprocessor {
+        disabled = state.in_disabled_code();
+        let at_line_start = !state.lex.in_comment() && state.lex.in_string.is_none();
+        if let Some((name, rest)) = directive(line).filter(|_| at_line_start) {
+            apply_directive(&mut state.conditionals, name, &line[rest..]);
+            // A directive line is live when either side of it is, so the
+            // comment on `#if 0` or `#endif` still counts.
+            disabled = disabled && state.in_disabled_code();
+            match name {
+                // `<sys/*.h>` is a header name, not a comment opener.
+                b"include" | b"include_next" | b"import" => {
+                    let open = skip_spaces(line, rest);
+                    if line.get(open) == Some(&b'<') {
+                        from = memchr(b'>', &line[open..]).map_or(open, |close| open + close + 1);
+                    }
+                }
+                // Diagnostic text is free prose, where `'` is an apostrophe.
+                b"error" | b"warning" => lex_spec = &C_DIAGNOSTIC,
+                _ => {}
+            }
+        }
+    }
+    find_ranges(
+        &line[from..],
+        &mut state.lex,
+        lex_spec,
+        &mut |start, end, kind| {
+            on_range(CommentRange {
+                start: from + start,
+                end: from + end,
+                kind,
+                spec,
+                language: None,
+                disabled,
+            });
+        },
+    );
+}
+
+// `#  if 0` -> the directive name and where its argument starts.
+fn directive(line: &[u8]) -> Option<(&[u8], usize)> {
+    let hash = skip_spaces(line, 0);
+    if line.get(hash) != Some(&b'#') {
+        return None;
+    }
+    let start = skip_spaces(line, hash + 1);
+    let len = line[start..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_alphabetic() || **byte == b'_')
+        .count();
+    (len > 0).then(|| (&line[start..start + len], start + len))
+}
+
+fn apply_directive(conditionals: &mut Vec<Conditional>, name: &[u8], rest: &[u8]) {
+    match name {
+        b"if" => {
+            let value = condition_value(rest);
+            conditionals.push(Conditional {
+                active: value != Some(false),
+                taken: value,
+            });
+        }
+        b"ifdef" | b"ifndef" => conditionals.push(Conditional {
+            active: true,
+            taken: None,
+        }),
+        b"elif" | b"elifdef" | b"elifndef" => {
+            let Some(group) = conditionals.last_mut() else {
+                return;
+            };
+            let value = if name == b"elif" {
+                condition_value(rest)
+            } else {
+                None
+            };
+            if group.taken == Some(true) {
+                group.active = false;
+            } else {
+                group.active = value != Some(false);
+                group.taken = match value {
+                    Some(true) => Some(true),
+                    _ => group.taken.and(value),
+                };
+            }
+        }
+        b"else" => {
+            if let Some(group) = conditionals.last_mut() {
+                group.active = group.taken != Some(true);
+                group.taken = group.taken.map(|_| true);
+            }
+        }
+        b"endif" => {
+            conditionals.pop();
+        }
+        _ => {}
+    }
+}
+
+// Only literal conditions are evaluated: `0`, `1`, `false`, `true`, possibly
+// parenthesized. Anything else depends on the build.
+fn condition_value(rest: &[u8]) -> Option<bool> {
+    let end = [&b"//"[..], b"/*"]
+        .iter()
+        .filter_map(|token| find_subslice_from(rest, token, 0))
+        .min()
+        .unwrap_or(rest.len());
+    let mut condition = rest[..end].trim_ascii();
+    while let Some(inner) = condition
+        .strip_prefix(b"(")
+        .and_then(|inner| inner.strip_suffix(b")"))
+    {
+        condition = inner.trim_ascii();
+    }
+    match condition {
+        b"0" | b"false" => Some(false),
+        b"1" | b"true" => Some(true),
+        _ => None,
+    }
+}
+
+fn skip_spaces(line: &[u8], mut idx: usize) -> usize {
+    while matches!(line.get(idx), Some(b' ' | b'\t')) {
+        idx += 1;
+    }
+    idx
 }
 
 fn find_ranges(
     line: &[u8],
-    state: &mut BlockState,
+    state: &mut LexState,
     spec: &SyntaxSpec,
-    on_range: &mut impl FnMut(usize, usize),
+    on_range: &mut impl FnMut(usize, usize, CommentKind),
 ) {
     let len = line.len();
     let mut cursor = 0;
@@ -346,7 +1516,7 @@ fn find_ranges(
             interesting.push(first);
         }
     }
-    if let Some(token) = spec.line_comment {
+    for token in spec.line_comment.iter().chain(&spec.alt_line_comment) {
         if let Some(&first) = token.first() {
             interesting.push(first);
         }
@@ -356,24 +1526,91 @@ fn find_ranges(
             interesting.push(first);
         }
     }
-    if spec.raw_string {
-        interesting.push(b'r');
+    for form in spec.raw_strings {
+        interesting.push(match form {
+            RawString::Hashed => b'r',
+            RawString::Parenthesized => b'R',
+            RawString::Verbatim => b'@',
+            RawString::QuoteRun => b'"',
+            RawString::Heredoc { .. } => b'<',
+        });
+    }
+    let interpolates = spec
+        .strings
+        .iter()
+        .any(|delim| delim.interpolation.is_some());
+    if interpolates {
+        for byte in *b"{}()[]:" {
+            interesting.push(byte);
+        }
+    }
+    if spec.char_literals {
+        interesting.push(b'\'');
+    }
+    if spec.long_brackets {
+        interesting.push(b'[');
+    }
+    if spec.dollar_quotes {
+        interesting.push(b'$');
+    }
+
+    if let Some(heredoc) = state.heredocs.first() {
+        if heredoc.closes(line) {
+            state.heredocs.remove(0);
+        }
+        return;
+    }
+
+    if let Some(block) = spec.line_block {
+        if state.in_line_block {
+            state.in_line_block = !starts_with(line, block.close, 0);
+            on_range(0, len, block.kind);
+            return;
+        }
+        let at_code = !state.in_comment() && state.in_string.is_none() && state.raw_close.is_none();
+        if at_code && block.open.iter().any(|open| starts_with(line, open, 0)) {
+            state.in_line_block = true;
+            on_range(0, len, block.kind);
+            return;
+        }
+    }
+
+    if spec.docstrings && spec.docstring_prefixes.is_empty() {
+        let at_code = !state.in_comment()
+            && state.in_string.is_none()
+            && state.raw_close.is_none()
+            && state.interpolations.is_empty();
+        if at_code {
+            note_statement(state, line, spec);
+        }
     }
 
     if state.in_block {
-        let Some((_, end_token)) = spec.block_comment else {
+        if spec.block_comment.is_none() {
             state.in_block = false;
             state.in_string = None;
             state.escape = false;
-            state.raw_string_hash = None;
+            state.raw_close = None;
             return;
-        };
-        if let Some(end) = find_subslice_from(line, end_token, cursor) {
-            on_range(cursor, end + end_token.len());
+        }
+        let kind = state.block_kind.unwrap_or(CommentKind::Block);
+        if let Some(end) = find_block_end(line, cursor, spec, &mut state.block_depth) {
+            on_range(cursor, end, kind);
             state.in_block = false;
+            state.block_kind = None;
+            cursor = end;
+        } else {
+            on_range(cursor, len, kind);
+            return;
+        }
+    } else if let Some(active) = state.docstring {
+        let end_token = spec.strings[active].token;
+        if let Some(end) = find_subslice_from(line, end_token, cursor) {
+            on_range(cursor, end + end_token.len(), CommentKind::Docstring);
+            state.docstring = None;
             cursor = end + end_token.len();
         } else {
-            on_range(cursor, len);
+            on_range(cursor, len, CommentKind::Docstring);
             return;
         }
     }
@@ -392,6 +1629,22 @@ fn find_ranges(
                 idx += 1;
                 continue;
             }
+            if let Some(interpolation) = delim.interpolation.filter(|_| state.string_interpolates) {
+                if starts_with(line, interpolation.open, idx) {
+                    // A doubled `{` is a literal brace.
+                    if interpolation.open == b"{" && line.get(idx + 1) == Some(&b'{') {
+                        idx += 2;
+                        continue;
+                    }
+                    state.interpolations.push(OpenInterpolation {
+                        string: active,
+                        depth: 0,
+                    });
+                    state.in_string = None;
+                    idx += interpolation.open.len();
+                    continue;
+                }
+            }
             if starts_with(line, delim.token, idx) {
                 state.in_string = None;
                 state.escape = false;
@@ -402,10 +1655,32 @@ fn find_ranges(
             continue;
         }
 
-        if let Some(hash_count) = state.raw_string_hash {
-            if let Some(end_idx) = find_raw_string_end(line, idx, hash_count) {
+        if let Some(open) = state.long_bracket {
+            let end = find_long_bracket_close(line, idx, open.level);
+            if open.comment {
+                on_range(idx, end.unwrap_or(len), CommentKind::Block);
+            }
+            let Some(end) = end else {
+                return;
+            };
+            state.long_bracket = None;
+            idx = end;
+            continue;
+        }
+
+        if let Some(tag) = &state.dollar_tag {
+            let Some(end) = find_subslice_from(line, tag, idx) else {
+                return;
+            };
+            idx = end + tag.len();
+            state.dollar_tag = None;
+            continue;
+        }
+
+        if let Some(close) = &state.raw_close {
+            if let Some(end_idx) = find_raw_close(line, idx, close) {
                 idx = end_idx;
-                state.raw_string_hash = None;
+                state.raw_close = None;
                 continue;
             }
             return;
@@ -419,38 +1694,148 @@ fn find_ranges(
             }
         }
 
-        if spec.raw_string {
-            if let Some((hash_count, consumed)) = parse_raw_string_start(line, idx) {
-                state.raw_string_hash = Some(hash_count);
-                idx += consumed;
+        if let Some(open) = state.interpolations.last_mut() {
+            let format_spec = spec.strings[open.string]
+                .interpolation
+                .is_some_and(|interpolation| interpolation.format_spec);
+            let handled = match line[idx] {
+                b'{' | b'(' | b'[' => {
+                    open.depth += 1;
+                    true
+                }
+                b'}' | b')' | b']' if open.depth > 0 => {
+                    open.depth -= 1;
+                    true
+                }
+                b'}' => {
+                    resume_string(state);
+                    true
+                }
+                b':' if open.depth == 0 && format_spec => {
+                    resume_string(state);
+                    true
+                }
+                _ => false,
+            };
+            if handled {
+                idx += 1;
+                continue;
+            }
+        }
+
+        if spec.char_literals && line[idx] == b'\'' {
+            idx = char_literal_end(line, idx).unwrap_or(idx + 1);
+            continue;
+        }
+
+        if spec.long_brackets {
+            let comment = spec
+                .line_comment
+                .filter(|token| starts_with(line, token, idx))
+                .map_or(0, <[u8]>::len);
+            if let Some((level, consumed)) = long_bracket_open(line, idx + comment) {
+                let body = idx + comment + consumed;
+                let end = find_long_bracket_close(line, body, level);
+                if comment > 0 {
+                    on_range(idx, end.unwrap_or(len), CommentKind::Block);
+                }
+                let Some(end) = end else {
+                    state.long_bracket = Some(LongBracket {
+                        level,
+                        comment: comment > 0,
+                    });
+                    return;
+                };
+                idx = end;
+                continue;
+            }
+        }
+
+        if spec.dollar_quotes {
+            if let Some(tag) = dollar_quote_open(line, idx) {
+                let body = idx + tag.len();
+                match find_subslice_from(line, tag, body) {
+                    Some(end) => idx = end + tag.len(),
+                    None => {
+                        state.dollar_tag = Some(tag.into());
+                        return;
+                    }
+                }
                 continue;
             }
         }
 
-        if let Some(string_idx) = find_string_start(line, spec.strings, idx) {
+        if let Some((open, consumed)) = raw_string_start(line, idx, spec.raw_strings) {
+            match open {
+                RawOpen::Close(close) => state.raw_close = Some(close),
+                RawOpen::Heredoc(heredoc) => state.heredocs.push(heredoc),
+            }
+            idx += consumed;
+            continue;
+        }
+
+        let string_start = find_string_start(line, spec.strings, idx).filter(|&string_idx| {
+            spec.line_comment != Some(spec.strings[string_idx].token)
+                || !quote_starts_comment(line, idx)
+        });
+        if let Some(string_idx) = string_start {
             let delim = &spec.strings[string_idx];
+            let prefix = string_prefix(line, idx, delim.prefixes);
+            if is_docstring_start(line, idx - prefix.len(), delim, spec, state) {
+                let body = idx + delim.token.len();
+                if let Some(end_idx) = find_subslice_from(line, delim.token, body) {
+                    on_range(idx, end_idx + delim.token.len(), CommentKind::Docstring);
+                    idx = end_idx + delim.token.len();
+                    continue;
+                }
+                on_range(idx, len, CommentKind::Docstring);
+                state.docstring = Some(string_idx);
+                return;
+            }
             state.in_string = Some(string_idx);
+            state.string_interpolates = delim.interpolation.is_some_and(|interpolation| {
+                interpolation.prefix.is_none_or(|letter| {
+                    prefix.iter().any(|byte| byte.eq_ignore_ascii_case(&letter))
+                })
+            });
             state.escape = false;
             idx += delim.token.len();
             continue;
         }
 
-        if let Some(token) = spec.line_comment {
-            if starts_with(line, token, idx) {
-                on_range(idx, len);
+        if spec.regex_literals
+            && line[idx] == b'/'
+            && !matches!(line.get(idx + 1), Some(b'/' | b'*'))
+            && regex_allowed(line, idx, state.after_operand)
+        {
+            if let Some(end) = regex_literal_end(line, idx) {
+                idx = end;
+                continue;
+            }
+        }
+
+        if let Some(token) = spec.line_comment_at(line, idx) {
+            if !opens_longer_block(line, idx, token, spec) && !is_dollar_hash(line, idx, token) {
+                on_range(idx, len, line_comment_kind(line, idx, spec));
+                note_code_end(state, line, idx, spec);
                 return;
             }
         }
 
-        if let Some((start, end)) = spec.block_comment {
+        if let Some((start, _)) = spec.block_comment {
             if starts_with(line, start, idx) {
-                if let Some(end_idx) = find_subslice_from(line, end, idx + start.len()) {
-                    on_range(idx, end_idx + end.len());
-                    idx = end_idx + end.len();
+                let kind = block_comment_kind(line, idx, spec);
+                let mut depth = 1;
+                if let Some(end_idx) = find_block_end(line, idx + start.len(), spec, &mut depth) {
+                    on_range(idx, end_idx, kind);
+                    idx = end_idx;
                     continue;
                 }
-                on_range(idx, len);
+                on_range(idx, len, kind);
+                note_code_end(state, line, idx, spec);
                 state.in_block = true;
+                state.block_depth = depth;
+                state.block_kind = Some(kind);
                 return;
             }
         }
@@ -464,6 +1849,632 @@ fn find_ranges(
             state.escape = false;
         }
     }
+    state
+        .interpolations
+        .retain(|open| spec.strings[open.string].multiline);
+    if state.in_string.is_none() {
+        note_code_end(state, line, len, spec);
+    }
+}
+
+// `$#` is a variable (Perl's last index, the shell's argument count).
+fn is_dollar_hash(line: &[u8], idx: usize, token: &[u8]) -> bool {
+    token == b"#" && idx > 0 && line[idx - 1] == b'$'
+}
+
+// When the comment token also opens strings, as Vim script's `"` does, it is a
+// comment at the start of a line or after whitespace that follows a complete
+// expression. After a lone command (`echo "x"`) or an operator it is a string.
+fn quote_starts_comment(line: &[u8], idx: usize) -> bool {
+    let code = line[..idx].trim_ascii();
+    let colons = code.iter().take_while(|byte| **byte == b':').count();
+    let command = code[colons..].trim_ascii_start();
+    if command.is_empty() {
+        return true;
+    }
+    let spaced = line[idx - 1].is_ascii_whitespace();
+    let operand = code.last().is_some_and(|&last| {
+        is_ident_byte(last) || matches!(last, b')' | b']' | b'}' | b'\'' | b'"')
+    });
+    spaced && operand && command.iter().any(u8::is_ascii_whitespace)
+}
+
+// End of a `'x'` or `'\n'` literal at `idx`. A quote after a name is a prime
+// (`x'`), and one not closed right after a character starts a type variable
+// (`'a`); neither is a literal.
+fn char_literal_end(line: &[u8], idx: usize) -> Option<usize> {
+    if idx > 0 && is_ident_byte(line[idx - 1]) {
+        return None;
+    }
+    let body = idx + 1;
+    let close = match *line.get(body)? {
+        b'\\' => body + 2 + memchr(b'\'', line.get(body + 2..)?)?,
+        first => body + utf8_len(first),
+    };
+    (line.get(close) == Some(&b'\'')).then_some(close + 1)
+}
+
+fn utf8_len(first: u8) -> usize {
+    match first {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    }
+}
+
+// Remembers whether the code on this line, up to `end`, left an operand
+// behind, for a regex that starts the next line.
+fn note_code_end(state: &mut LexState, line: &[u8], end: usize, spec: &SyntaxSpec) {
+    if spec.regex_literals && !line[..end].trim_ascii().is_empty() {
+        state.after_operand = !regex_allowed(line, end, true);
+    }
+}
+
+// A `/` starts a regex unless it follows an operand: a name, a number, a
+// closing bracket or a string. Keywords such as `return` are not operands.
+fn regex_allowed(line: &[u8], idx: usize, after_operand: bool) -> bool {
+    // `</` closes a JSX element.
+    if idx > 0 && line[idx - 1] == b'<' {
+        return false;
+    }
+    let code = line[..idx].trim_ascii_end();
+    let Some(&last) = code.last() else {
+        return !after_operand;
+    };
+    if is_ident_byte(last) || last == b'$' {
+        let word = code
+            .iter()
+            .rev()
+            .take_while(|byte| is_ident_byte(**byte) || **byte == b'$')
+            .count();
+        return REGEX_KEYWORDS.contains(&&code[code.len() - word..]);
+    }
+    !matches!(last, b')' | b']' | b'"' | b'\'' | b'`')
+}
+
+const REGEX_KEYWORDS: &[&[u8]] = &[
+    b"return",
+    b"typeof",
+    b"instanceof",
+    b"in",
+    b"of",
+    b"new",
+    b"delete",
+    b"void",
+    b"throw",
+    b"case",
+    b"do",
+    b"else",
+    b"yield",
+    b"await",
+];
+
+// Returns the index past a regex literal and its flags. A `/` inside a
+// character class does not close it, and a regex never spans lines.
+fn regex_literal_end(line: &[u8], idx: usize) -> Option<usize> {
+    let mut pos = idx + 1;
+    let mut in_class = false;
+    while pos < line.len() {
+        match line[pos] {
+            b'\\' => pos += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'/' if !in_class => {
+                let flags = line[pos + 1..]
+                    .iter()
+                    .take_while(|byte| byte.is_ascii_alphabetic())
+                    .count();
+                return Some(pos + 1 + flags);
+            }
+            b'\n' | b'\r' => return None,
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+// Leaves the innermost interpolation and continues its string.
+fn resume_string(state: &mut LexState) {
+    if let Some(open) = state.interpolations.pop() {
+        state.in_string = Some(open.string);
+        state.string_interpolates = true;
+        state.escape = false;
+    }
+}
+
+// Prefix letters directly before a string token (`rb` in `rb"..."`). Empty
+// unless the whole word before the token is made of them.
+fn string_prefix<'l>(line: &'l [u8], idx: usize, prefixes: &[u8]) -> &'l [u8] {
+    if prefixes.is_empty() {
+        return &[];
+    }
+    let word = line[..idx]
+        .iter()
+        .rev()
+        .take_while(|byte| is_ident_byte(**byte))
+        .count();
+    let prefix = &line[idx - word..idx];
+    let valid = prefix.len() <= prefixes.len()
+        && prefix
+            .iter()
+            .all(|byte| prefixes.contains(&byte.to_ascii_lowercase()));
+    if valid { prefix } else { &[] }
+}
+
+// Markup comments are `<!-- -->`; quotes only delimit attribute values inside
+// tags, and CDATA sections are opaque. Embedded regions hand their text to the
+// region's own spec. Markdown is prose, so a `<` right after a word is not a
+// tag, and a tag whose quote is still open at the end of a line was prose too.
+fn find_markup_ranges<'a>(
+    line: &[u8],
+    state: &mut BlockState,
+    spec: &'a SyntaxSpec,
+    on_range: &mut impl FnMut(CommentRange<'a>),
+) {
+    let len = line.len();
+    let mut idx = 0;
+    let prose = spec
+        .regions
+        .iter()
+        .any(|region| matches!(region.open, RegionOpen::CodeFence));
+    let comment = |start: usize, end: usize| CommentRange {
+        start,
+        end,
+        kind: CommentKind::Block,
+        spec,
+        language: None,
+        disabled: false,
+    };
+
+    if !state.started {
+        state.started = true;
+        let fenced = spec.regions.iter().find(|region| {
+            matches!(region.open, RegionOpen::Frontmatter(fence) if line.trim_ascii() == fence)
+        });
+        if let Some(region) = fenced {
+            state.region = Some(ActiveRegion::new(region, region.spec, region.language));
+            return;
+        }
+    }
+
+    if state.region.is_none() && !state.lex.in_block && !state.lex.in_tag {
+        if let Some(region) = code_fence_start(line, spec.regions) {
+            state.region = Some(region);
+            return;
+        }
+    }
+
+    while idx < len {
+        if let Some(region) = state.region {
+            idx = scan_region(line, idx, region, state, on_range);
+            continue;
+        }
+        let lex = &mut state.lex;
+        if lex.in_block {
+            if let Some(end) = find_subslice_from(line, b"-->", idx) {
+                on_range(comment(idx, end + 3));
+                lex.in_block = false;
+                idx = end + 3;
+                continue;
+            }
+            on_range(comment(idx, len));
+            return;
+        }
+        if lex.in_cdata {
+            let Some(end) = find_subslice_from(line, b"]]>", idx) else {
+                return;
+            };
+            lex.in_cdata = false;
+            idx = end + 3;
+            continue;
+        }
+        if let Some(quote) = lex.attr_quote {
+            let Some(offset) = memchr(quote, &line[idx..]) else {
+                if prose {
+                    lex.attr_quote = None;
+                    lex.in_tag = false;
+                    state.pending_region = None;
+                }
+                return;
+            };
+            lex.attr_quote = None;
+            idx += offset + 1;
+            continue;
+        }
+        if lex.in_tag {
+            let Some(offset) = memchr3(b'"', b'\'', b'>', &line[idx..]) else {
+                return;
+            };
+            idx += offset;
+            if line[idx] == b'>' {
+                lex.in_tag = false;
+                let pending = state.pending_region.take();
+                if line[idx - 1] != b'/' {
+                    state.region = pending;
+                }
+            } else {
+                lex.attr_quote = Some(line[idx]);
+            }
+            idx += 1;
+            continue;
+        }
+
+        let Some(offset) = memchr(b'<', &line[idx..]) else {
+            return;
+        };
+        idx += offset;
+        if starts_with(line, b"<!--", idx) {
+            if let Some(end) = find_subslice_from(line, b"-->", idx + 4) {
+                on_range(comment(idx, end + 3));
+                idx = end + 3;
+                continue;
+            }
+            on_range(comment(idx, len));
+            lex.in_block = true;
+            return;
+        }
+        if starts_with(line, b"<![CDATA[", idx) {
+            lex.in_cdata = true;
+            idx += 9;
+            continue;
+        }
+        if let Some((region, consumed)) = region_start(line, idx, spec.regions) {
+            match region.open {
+                RegionOpen::Tag(_) => {
+                    state.pending_region = Some(region);
+                    lex.in_tag = true;
+                }
+                _ => state.region = Some(region),
+            }
+            idx += consumed;
+            continue;
+        }
+        let after_word = prose && idx > 0 && is_ident_byte(line[idx - 1]);
+        if !after_word && is_tag_start(line, idx) {
+            lex.in_tag = true;
+            idx += 2;
+            continue;
+        }
+        idx += 1;
+    }
+}
+
+// `<name`, `</name`, `<?` or `<!` at `idx`.
+fn is_tag_start(line: &[u8], idx: usize) -> bool {
+    match line.get(idx + 1) {
+        Some(b'/') => line.get(idx + 2).is_some_and(u8::is_ascii_alphabetic),
+        Some(next) => next.is_ascii_alphabetic() || matches!(next, b'?' | b'!'),
+        None => false,
+    }
+}
+
+impl ActiveRegion {
+    fn new(
+        region: &EmbeddedRegion,
+        spec: &'static SyntaxSpec<'static>,
+        language: &'static str,
+    ) -> Self {
+        Self {
+            open: region.open,
+            spec,
+            language,
+            fence: None,
+        }
+    }
+}
+
+fn region_start(
+    line: &[u8],
+    idx: usize,
+    regions: &[EmbeddedRegion],
+) -> Option<(ActiveRegion, usize)> {
+    for region in regions {
+        match region.open {
+            RegionOpen::Tag(name) => {
+                let name_end = idx + 1 + name.len();
+                let Some(candidate) = line.get(idx + 1..name_end) else {
+                    continue;
+                };
+                let boundary = line
+                    .get(name_end)
+                    .is_none_or(|&next| next.is_ascii_whitespace() || matches!(next, b'>' | b'/'));
+                if candidate.eq_ignore_ascii_case(name) && boundary {
+                    let tag_end = memchr(b'>', &line[idx..]).map_or(line.len(), |end| idx + end);
+                    let lang = tag_attribute(&line[name_end..tag_end], b"lang");
+                    let variant = lang.and_then(|lang| {
+                        region
+                            .variants
+                            .iter()
+                            .find(|variant| variant.lang.eq_ignore_ascii_case(lang))
+                    });
+                    let active = match variant {
+                        Some(variant) => ActiveRegion::new(region, variant.spec, variant.language),
+                        None => ActiveRegion::new(region, region.spec, region.language),
+                    };
+                    return Some((active, 1 + name.len()));
+                }
+            }
+            RegionOpen::Delimited(open, _) => {
+                if starts_with(line, open, idx) {
+                    let active = ActiveRegion::new(region, region.spec, region.language);
+                    return Some((active, open.len()));
+                }
+            }
+            RegionOpen::Frontmatter(_) | RegionOpen::CodeFence => {}
+        }
+    }
+    None
+}
+
+// Opens a code fence region; the info string picks the block's language, and
+// blocks in an unknown language have no comments.
+fn code_fence_start(line: &[u8], regions: &[EmbeddedRegion]) -> Option<ActiveRegion> {
+    let region = regions
+        .iter()
+        .find(|region| matches!(region.open, RegionOpen::CodeFence))?;
+    let (fence, len, info) = code_fence(line)?;
+    let info = info.strip_prefix(b"{").unwrap_or(info);
+    let info = info.strip_prefix(b".").unwrap_or(info);
+    let name_len = info
+        .iter()
+        .position(|byte| {
+            !(byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'#' | b'-' | b'_'))
+        })
+        .unwrap_or(info.len());
+    let syntax = std::str::from_utf8(&info[..name_len])
+        .ok()
+        .filter(|name| !name.is_empty())
+        .and_then(syntax_for_language);
+    let mut active = match syntax {
+        Some(info) => ActiveRegion::new(region, info.spec, info.language),
+        None => ActiveRegion::new(region, region.spec, region.language),
+    };
+    active.fence = Some((fence, len));
+    Some(active)
+}
+
+// CommonMark fence line: up to three spaces of indent, then three or more
+// backticks or tildes, then the info string.
+fn code_fence(line: &[u8]) -> Option<(u8, usize, &[u8])> {
+    let indent = line.iter().take_while(|&&byte| byte == b' ').count();
+    if indent > 3 {
+        return None;
+    }
+    let fence = *line.get(indent)?;
+    if fence != b'`' && fence != b'~' {
+        return None;
+    }
+    let len = line[indent..]
+        .iter()
+        .take_while(|&&byte| byte == fence)
+        .count();
+    let info = &line[indent + len..];
+    if len < 3 || (fence == b'`' && info.contains(&b'`')) {
+        return None;
+    }
+    Some((fence, len, info.trim_ascii()))
+}
+
+// Scans region text from `idx` up to the region's end on this line and
+// returns where the host resumes.
+fn scan_region<'a>(
+    line: &[u8],
+    idx: usize,
+    region: ActiveRegion,
+    state: &mut BlockState,
+    on_range: &mut impl FnMut(CommentRange<'a>),
+) -> usize {
+    let (end, resume) = match region.open {
+        RegionOpen::Tag(name) => match find_closing_tag(line, idx, name) {
+            Some(end) => (end, end),
+            None => (line.len(), line.len()),
+        },
+        RegionOpen::Delimited(_, close) => match find_subslice_from(line, close, idx) {
+            Some(end) => (end, end + close.len()),
+            None => (line.len(), line.len()),
+        },
+        RegionOpen::Frontmatter(fence) => {
+            if line.trim_ascii() == fence {
+                state.region = None;
+                state.embedded = LexState::default();
+                return line.len();
+            }
+            (line.len(), line.len())
+        }
+        RegionOpen::CodeFence => {
+            let closes = code_fence(line).is_some_and(|(fence, len, info)| {
+                region
+                    .fence
+                    .is_some_and(|(open, open_len)| fence == open && len >= open_len)
+                    && info.is_empty()
+            });
+            if closes {
+                state.region = None;
+                state.embedded = LexState::default();
+                return line.len();
+            }
+            (line.len(), line.len())
+        }
+    };
+
+    find_ranges(
+        &line[idx..end],
+        &mut state.embedded,
+        region.spec,
+        &mut |start, range_end, kind| {
+            on_range(CommentRange {
+                start: idx + start,
+                end: idx + range_end,
+                kind,
+                spec: region.spec,
+                language: Some(region.language),
+                disabled: false,
+            });
+        },
+    );
+    if end < line.len() {
+        state.region = None;
+        state.embedded = LexState::default();
+    }
+    resume
+}
+
+fn find_closing_tag(line: &[u8], from: usize, name: &[u8]) -> Option<usize> {
+    let mut idx = from;
+    while let Some(offset) = find_subslice_from(line, b"</", idx) {
+        let start = offset + 2;
+        if line
+            .get(start..start + name.len())
+            .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+        {
+            return Some(offset);
+        }
+        idx = offset + 2;
+    }
+    None
+}
+
+// Reads an attribute value such as `lang="ts"` from the inside of a tag.
+fn tag_attribute<'l>(tag: &'l [u8], name: &[u8]) -> Option<&'l [u8]> {
+    let mut idx = 0;
+    while let Some(offset) = find_subslice_from(tag, name, idx) {
+        let at = offset;
+        idx = at + name.len();
+        if at > 0 && !tag[at - 1].is_ascii_whitespace() {
+            continue;
+        }
+        let mut pos = idx;
+        while pos < tag.len() && tag[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if tag.get(pos) != Some(&b'=') {
+            continue;
+        }
+        pos += 1;
+        while pos < tag.len() && tag[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        let value = match tag.get(pos) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let rest = &tag[pos + 1..];
+                &rest[..memchr(quote, rest).unwrap_or(rest.len())]
+            }
+            Some(_) => {
+                let rest = &tag[pos..];
+                let end = rest
+                    .iter()
+                    .position(|byte| byte.is_ascii_whitespace() || matches!(byte, b'>' | b'/'))
+                    .unwrap_or(rest.len());
+                &rest[..end]
+            }
+            None => return None,
+        };
+        return Some(value);
+    }
+    None
+}
+
+// Returns the index just past the token that closes the block comment. With
+// nesting, every opening token needs its own close; `depth` carries the open
+// count across lines.
+fn find_block_end(line: &[u8], from: usize, spec: &SyntaxSpec, depth: &mut u32) -> Option<usize> {
+    let (open, close) = spec.block_comment?;
+    if !spec.nested_blocks {
+        return find_subslice_from(line, close, from).map(|end| end + close.len());
+    }
+    let mut idx = from;
+    while idx < line.len() {
+        if starts_with(line, close, idx) {
+            idx += close.len();
+            *depth = depth.saturating_sub(1);
+            if *depth == 0 {
+                return Some(idx);
+            }
+        } else if starts_with(line, open, idx) {
+            idx += open.len();
+            *depth += 1;
+        } else {
+            idx += 1;
+        }
+    }
+    None
+}
+
+// Julia's `#=` opens a block even though `#` starts a line comment.
+pub(crate) fn opens_longer_block(line: &[u8], idx: usize, token: &[u8], spec: &SyntaxSpec) -> bool {
+    spec.block_comment
+        .is_some_and(|(start, _)| start.len() > token.len() && starts_with(line, start, idx))
+}
+
+// A doc token followed by its own last byte (`////`, `/***`, `/**/`) is a
+// regular comment.
+fn is_doc_token(line: &[u8], idx: usize, tokens: &[&[u8]]) -> bool {
+    tokens.iter().any(|token| {
+        starts_with(line, token, idx) && !matches!(line.get(idx + token.len()), Some(b'/' | b'*'))
+    })
+}
+
+fn line_comment_kind(line: &[u8], idx: usize, spec: &SyntaxSpec) -> CommentKind {
+    if is_doc_token(line, idx, spec.doc_line) {
+        CommentKind::DocLine
+    } else {
+        CommentKind::Line
+    }
+}
+
+fn block_comment_kind(line: &[u8], idx: usize, spec: &SyntaxSpec) -> CommentKind {
+    if is_doc_token(line, idx, spec.doc_block) {
+        CommentKind::DocBlock
+    } else {
+        CommentKind::Block
+    }
+}
+
+// Triple-quoted strings that open a line are treated as docstrings when they
+// sit where Python reads one: first in the module or in a `def`/`class` body.
+fn is_docstring_start(
+    line: &[u8],
+    idx: usize,
+    delim: &StringDelim,
+    spec: &SyntaxSpec,
+    state: &LexState,
+) -> bool {
+    let before = line[..idx].trim_ascii();
+    let placed = if spec.docstring_prefixes.is_empty() {
+        before.is_empty() && state.docstring_allowed
+    } else {
+        spec.docstring_prefixes.contains(&before)
+    };
+    spec.docstrings && delim.multiline && delim.token.len() == 3 && placed
+}
+
+// Called at the start of each line that begins in code. Blank and comment
+// lines leave the statement position alone.
+fn note_statement(state: &mut LexState, line: &[u8], spec: &SyntaxSpec) {
+    let code = line.trim_ascii();
+    let comment = spec
+        .line_comment
+        .is_some_and(|token| code.starts_with(token));
+    if code.is_empty() || comment {
+        return;
+    }
+    state.docstring_allowed = !state.code_started || state.body_opened;
+    state.code_started = true;
+    let header = [&b"def "[..], b"class ", b"async def "]
+        .iter()
+        .any(|keyword| code.starts_with(keyword));
+    let code = spec
+        .line_comment
+        .and_then(|token| find_subslice_from(code, token, 0))
+        .map_or(code, |end| code[..end].trim_ascii_end());
+    state.body_opened = (header || state.in_header) && code.ends_with(b":");
+    // A signature split over several lines keeps the header open until the
+    // line that ends in `:`.
+    let opens = code.iter().filter(|byte| **byte == b'(').count();
+    let closes = code.iter().filter(|byte| **byte == b')').count();
+    state.in_header = !state.body_opened && (state.in_header || (header && opens > closes));
 }
 
 fn find_next_interesting(line: &[u8], start: usize, interesting: &[u8]) -> Option<usize> {
@@ -556,45 +2567,209 @@ fn find_subslice_from(haystack: &[u8], needle: &[u8], start: usize) -> Option<us
     None
 }
 
-fn parse_raw_string_start(line: &[u8], idx: usize) -> Option<(usize, usize)> {
-    if idx >= line.len() || line[idx] != b'r' {
+// Parses `[[` or `[=*[` and returns the level and the opening length.
+pub(crate) fn long_bracket_open(line: &[u8], idx: usize) -> Option<(usize, usize)> {
+    if line.get(idx) != Some(&b'[') {
+        return None;
+    }
+    let level = line[idx + 1..]
+        .iter()
+        .take_while(|&&byte| byte == b'=')
+        .count();
+    (line.get(idx + 1 + level) == Some(&b'[')).then_some((level, level + 2))
+}
+
+// Returns the index just past `]=*]` with a matching level.
+fn find_long_bracket_close(line: &[u8], from: usize, level: usize) -> Option<usize> {
+    let mut idx = from;
+    while let Some(offset) = line.get(idx..).and_then(|rest| memchr(b']', rest)) {
+        let start = idx + offset;
+        let equals = line[start + 1..]
+            .iter()
+            .take_while(|&&byte| byte == b'=')
+            .count();
+        if equals == level && line.get(start + 1 + level) == Some(&b']') {
+            return Some(start + level + 2);
+        }
+        idx = start + 1;
+    }
+    None
+}
+
+// Parses a PostgreSQL dollar-quote opener such as `$$` or `$body$`.
+fn dollar_quote_open(line: &[u8], idx: usize) -> Option<&[u8]> {
+    if line.get(idx) != Some(&b'$') {
         return None;
     }
-    if idx > 0 && line[idx - 1].is_ascii_alphanumeric() {
+    if idx > 0 && (line[idx - 1].is_ascii_alphanumeric() || line[idx - 1] == b'_') {
         return None;
     }
-    let mut pos = idx + 1;
-    let mut hashes = 0usize;
-    while pos < line.len() && line[pos] == b'#' {
-        hashes += 1;
-        pos += 1;
+    let tag_len = line[idx + 1..]
+        .iter()
+        .take_while(|&&byte| byte.is_ascii_alphanumeric() || byte == b'_')
+        .count();
+    if tag_len > 0 && line[idx + 1].is_ascii_digit() {
+        return None;
     }
-    if pos < line.len() && line[pos] == b'"' {
-        return Some((hashes, pos - idx + 1));
+    let end = idx + 1 + tag_len;
+    (line.get(end) == Some(&b'$')).then(|| &line[idx..=end])
+}
+
+// Opens one of the spec's raw literal forms at `idx` and returns how many
+// bytes the opener takes.
+fn raw_string_start(line: &[u8], idx: usize, forms: &[RawString]) -> Option<(RawOpen, usize)> {
+    forms.iter().find_map(|form| match form {
+        RawString::Hashed => hashed_raw_start(line, idx),
+        RawString::Parenthesized => parenthesized_raw_start(line, idx),
+        RawString::Verbatim => verbatim_start(line, idx),
+        RawString::QuoteRun => quote_run_start(line, idx),
+        RawString::Heredoc { spaced } => heredoc_start(line, idx, *spaced)
+            .map(|(heredoc, consumed)| (RawOpen::Heredoc(heredoc), consumed)),
+    })
+}
+
+// `r"`, `r#"`, also after a `b` or `c` prefix.
+fn hashed_raw_start(line: &[u8], idx: usize) -> Option<(RawOpen, usize)> {
+    if line.get(idx) != Some(&b'r') {
+        return None;
     }
-    None
+    let word_start = match idx.checked_sub(1).map(|before| line[before]) {
+        Some(b'b' | b'c') => idx - 1,
+        _ => idx,
+    };
+    if word_start > 0 && is_ident_byte(line[word_start - 1]) {
+        return None;
+    }
+    let hashes = line[idx + 1..]
+        .iter()
+        .take_while(|&&byte| byte == b'#')
+        .count();
+    if line.get(idx + 1 + hashes) != Some(&b'"') {
+        return None;
+    }
+    let mut token = vec![b'"'];
+    token.resize(1 + hashes, b'#');
+    Some((raw_close(token, false), hashes + 2))
 }
 
-fn find_raw_string_end(line: &[u8], start: usize, hashes: usize) -> Option<usize> {
-    if start >= line.len() {
+// `R"delim(`, optionally after an encoding prefix (`u8R"(`).
+fn parenthesized_raw_start(line: &[u8], idx: usize) -> Option<(RawOpen, usize)> {
+    if !starts_with(line, b"R\"", idx) {
         return None;
     }
-    let mut idx = start;
-    while idx < line.len() {
-        let offset = memchr(b'"', &line[idx..])?;
-        idx += offset;
-        let mut ok = true;
-        for offset in 0..hashes {
-            let pos = idx + 1 + offset;
-            if pos >= line.len() || line[pos] != b'#' {
-                ok = false;
-                break;
-            }
+    let prefix = [&b"u8"[..], b"u", b"U", b"L"]
+        .iter()
+        .find(|prefix| idx >= prefix.len() && line[..idx].ends_with(prefix))
+        .map_or(0, |prefix| prefix.len());
+    let word_start = idx - prefix;
+    if word_start > 0 && is_ident_byte(line[word_start - 1]) {
+        return None;
+    }
+    let open = idx + 2;
+    let delim_len = memchr(b'(', &line[open..])?;
+    let delim = &line[open..open + delim_len];
+    if delim_len > 16
+        || delim
+            .iter()
+            .any(|byte| byte.is_ascii_whitespace() || matches!(byte, b')' | b'\\' | b'"'))
+    {
+        return None;
+    }
+    let mut token = vec![b')'];
+    token.extend_from_slice(delim);
+    token.push(b'"');
+    Some((raw_close(token, false), delim_len + 3))
+}
+
+// `@"` or `@$"`.
+fn verbatim_start(line: &[u8], idx: usize) -> Option<(RawOpen, usize)> {
+    if line.get(idx) != Some(&b'@') {
+        return None;
+    }
+    let quote = idx + 1 + usize::from(line.get(idx + 1) == Some(&b'$'));
+    (line.get(quote) == Some(&b'"')).then(|| (raw_close(b"\"".to_vec(), true), quote + 1 - idx))
+}
+
+fn quote_run_start(line: &[u8], idx: usize) -> Option<(RawOpen, usize)> {
+    let quotes = line[idx..].iter().take_while(|&&byte| byte == b'"').count();
+    (quotes >= 3).then(|| (raw_close(vec![b'"'; quotes], false), quotes))
+}
+
+// `<<EOF`, `<<-EOF`, `<<~EOF`, `<<'EOF'`; `<<<` is a here-string. A `<<` right
+// after an operand or inside arithmetic (`$((1<<n))`) is a shift.
+fn heredoc_start(line: &[u8], idx: usize, spaced: bool) -> Option<(HeredocTag, usize)> {
+    if !starts_with(line, b"<<", idx)
+        || line.get(idx + 2) == Some(&b'<')
+        || (idx > 0 && (line[idx - 1] == b'<' || is_operand_end(line[idx - 1])))
+        || in_arithmetic(&line[..idx])
+    {
+        return None;
+    }
+    let mut pos = idx + 2;
+    let indented = matches!(line.get(pos), Some(b'-' | b'~'));
+    if indented {
+        pos += 1;
+    }
+    if spaced {
+        pos = skip_spaces(line, pos);
+    }
+    let (tag, end) = match *line.get(pos)? {
+        quote @ (b'\'' | b'"') => {
+            let len = memchr(quote, &line[pos + 1..])?;
+            (&line[pos + 1..pos + 1 + len], pos + len + 2)
         }
-        if ok {
-            return Some(idx + 1 + hashes);
+        byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+            let len = line[pos..]
+                .iter()
+                .take_while(|byte| is_ident_byte(**byte))
+                .count();
+            (&line[pos..pos + len], pos + len)
         }
-        idx += 1;
+        _ => return None,
+    };
+    if tag.is_empty() {
+        return None;
     }
-    None
+    let heredoc = HeredocTag {
+        tag: tag.into(),
+        indented,
+    };
+    Some((heredoc, end - idx))
+}
+
+fn is_operand_end(byte: u8) -> bool {
+    is_ident_byte(byte) || byte == b')'
+}
+
+// Whether `before` leaves an `((` open, as in `$((` or `((`.
+fn in_arithmetic(before: &[u8]) -> bool {
+    let Some(open) = before.windows(2).rposition(|pair| pair == b"((") else {
+        return false;
+    };
+    let rest = &before[open..];
+    memchr::memchr_iter(b'(', rest).count() > memchr::memchr_iter(b')', rest).count()
+}
+
+fn raw_close(token: Vec<u8>, doubled: bool) -> RawOpen {
+    RawOpen::Close(RawClose {
+        token: token.into(),
+        doubled,
+    })
+}
+
+// Returns the index just past the closing token.
+fn find_raw_close(line: &[u8], from: usize, close: &RawClose) -> Option<usize> {
+    let mut idx = from;
+    loop {
+        let end = find_subslice_from(line, &close.token, idx)?;
+        if close.doubled && line.get(end + 1) == Some(&close.token[0]) {
+            idx = end + 2;
+            continue;
+        }
+        return Some(end + close.token.len());
+    }
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
 }
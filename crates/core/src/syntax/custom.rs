@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::syntax::comments::{StringDelim, SyntaxSpec};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageDefinition {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<String>,
+    pub line_comment: Option<String>,
+    pub block_comment: Option<(String, String)>,
+    pub nested_blocks: bool,
+    pub strings: Vec<StringDefinition>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StringDefinition {
+    pub token: String,
+    pub escape: bool,
+    pub multiline: bool,
+}
+
+impl Default for StringDefinition {
+    fn default() -> Self {
+        Self {
+            token: String::new(),
+            escape: true,
+            multiline: false,
+        }
+    }
+}
+
+// A language declared at runtime. It owns its tokens and lends them out as a
+// `SyntaxSpec` while a file is scanned.
+#[derive(Clone, Debug)]
+pub(crate) struct CustomSyntax {
+    name: String,
+    extensions: Vec<String>,
+    filenames: Vec<String>,
+    line_comment: Option<Vec<u8>>,
+    block_comment: Option<(Vec<u8>, Vec<u8>)>,
+    nested_blocks: bool,
+    strings: Vec<(Vec<u8>, bool, bool)>,
+}
+
+impl CustomSyntax {
+    pub(crate) fn new(definition: &LanguageDefinition) -> Self {
+        let token = |value: &str| (!value.is_empty()).then(|| value.as_bytes().to_vec());
+        Self {
+            name: definition.name.trim().to_string(),
+            extensions: definition
+                .extensions
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            filenames: definition
+                .filenames
+                .iter()
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            line_comment: definition.line_comment.as_deref().and_then(token),
+            block_comment: definition
+                .block_comment
+                .as_ref()
+                .and_then(|(start, end)| Some((token(start)?, token(end)?))),
+            nested_blocks: definition.nested_blocks,
+            strings: definition
+                .strings
+                .iter()
+                .filter_map(|string| Some((token(&string.token)?, string.escape, string.multiline)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn string_delims(&self) -> Vec<StringDelim<'_>> {
+        self.strings
+            .iter()
+            .map(|(token, escape, multiline)| StringDelim {
+                token,
+                multiline: *multiline,
+                escape: *escape,
+                prefixes: &[],
+                interpolation: None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn spec<'a>(&'a self, strings: &'a [StringDelim<'a>]) -> SyntaxSpec<'a> {
+        SyntaxSpec {
+            line_comment: self.line_comment.as_deref(),
+            alt_line_comment: None,
+            block_comment: self
+                .block_comment
+                .as_ref()
+                .map(|(start, end)| (start.as_slice(), end.as_slice())),
+            strings,
+            raw_strings: &[],
+            doc_line: &[],
+            doc_block: &[],
+            docstrings: false,
+            nested_blocks: self.nested_blocks,
+            long_brackets: false,
+            dollar_quotes: false,
+            preprocessor: false,
+            regex_literals: false,
+            char_literals: false,
+            line_block: None,
+            docstring_prefixes: &[],
+            markup: false,
+            regions: &[],
+        }
+    }
+
+    fn matches(&self, name: Option<&str>, ext: Option<&str>) -> bool {
+        name.is_some_and(|name| self.filenames.iter().any(|candidate| candidate == name))
+            || ext.is_some_and(|ext| self.extensions.iter().any(|candidate| candidate == ext))
+    }
+}
+
+// File names take precedence over extensions, and earlier definitions over
+// later ones.
+pub(crate) fn custom_syntax_for_path<'a>(
+    path: &Path,
+    languages: &'a [CustomSyntax],
+) -> Option<&'a CustomSyntax> {
+    if languages.is_empty() {
+        return None;
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase());
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    languages
+        .iter()
+        .find(|language| language.matches(name.as_deref(), None))
+        .or_else(|| {
+            languages
+                .iter()
+                .find(|language| language.matches(None, ext.as_deref()))
+        })
+}
+
+pub(crate) fn custom_syntax_named<'a>(
+    name: &str,
+    languages: &'a [CustomSyntax],
+) -> Option<&'a CustomSyntax> {
+    languages
+        .iter()
+        .find(|language| language.name.eq_ignore_ascii_case(name.trim()))
+}
@@ -0,0 +1,94 @@
+use crate::syntax::comments::SyntaxInfo;
+use crate::syntax::language::Language;
+
+// Emacs mode lines belong on the first line (second after a shebang); Vim
+// looks at the first and last few lines, of which only the head is read.
+const MODELINE_LINES: usize = 5;
+
+// Picks a syntax for a file whose name says nothing, from a shebang or an
+// Emacs/Vim modeline near the top of the file.
+pub(crate) fn syntax_for_content(head: &[u8]) -> Option<SyntaxInfo> {
+    let mut lines = head.split(|byte| *byte == b'\n').take(MODELINE_LINES);
+    let first = lines.next()?;
+    if let Some(info) = shebang_interpreter(first).and_then(syntax_for_language) {
+        return Some(info);
+    }
+    std::iter::once(first)
+        .chain(lines)
+        .find_map(|line| modeline_mode(line).and_then(syntax_for_language))
+}
+
+// `#!/usr/bin/python3`, `#!/usr/bin/env -S node --flag` -> `python3`, `node`.
+fn shebang_interpreter(line: &[u8]) -> Option<&str> {
+    let line = std::str::from_utf8(line.strip_prefix(b"#!")?).ok()?;
+    let mut words = line.split_ascii_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    if program != "env" {
+        return Some(program);
+    }
+    words.find(|word| !word.starts_with('-') && !word.contains('='))
+}
+
+fn modeline_mode(line: &[u8]) -> Option<&str> {
+    let line = std::str::from_utf8(line).ok()?;
+    emacs_mode(line).or_else(|| vim_filetype(line))
+}
+
+// `-*- python -*-` or `-*- mode: ruby; coding: utf-8 -*-`.
+fn emacs_mode(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (vars, _) = rest.split_once("-*-")?;
+    if !vars.contains(':') {
+        return Some(vars.trim());
+    }
+    vars.split(';').find_map(|var| {
+        let (key, value) = var.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then(|| value.trim())
+    })
+}
+
+// `vim: set ft=python:`, `vi: filetype=sh`, `ex: syntax=ruby`.
+fn vim_filetype(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(idx, _)| {
+                line[..*idx]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+            })
+            .map(|(idx, _)| idx + marker.len())
+    })?;
+    line[start..]
+        .split(|ch: char| ch.is_whitespace() || ch == ':')
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syn" | "syntax").then_some(value)
+        })
+}
+
+// Maps interpreter and editor mode names, as well as language names and
+// aliases, onto the built-in table.
+pub(crate) fn syntax_for_language(name: &str) -> Option<SyntaxInfo> {
+    let name = name.trim().to_ascii_lowercase();
+    let name = name.strip_suffix("-mode").unwrap_or(&name);
+    // Versioned interpreters: `python3.12`, `ruby2.7`, `lua5.4`.
+    let base = name.trim_end_matches(|ch: char| ch.is_ascii_digit() || ch == '.');
+    let name = match base {
+        "python" | "pypy" => "python",
+        "sh" | "ksh" | "dash" | "ash" | "mksh" | "shell-script" => "shell",
+        "ruby" | "jruby" => "ruby",
+        "nodejs" | "deno" | "bun" => "javascript",
+        "ts-node" => "typescript",
+        "lua" | "luajit" => "lua",
+        "runghc" | "runhaskell" => "haskell",
+        "tuareg" => "ocaml",
+        "sbcl" | "clisp" | "ecl" | "emacs" => "lisp",
+        "csi" | "chicken" => "scheme",
+        "bb" => "clojure",
+        _ => name,
+    };
+    Language::find(name).map(Language::syntax)
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use crate::syntax::comments::{LANGUAGES, SyntaxInfo, SyntaxSpec};
+
+// Identity of a built-in language: the canonical name shown in results and
+// group labels, plus the other spellings (extensions, common short names)
+// accepted wherever a language is named.
+#[derive(Clone, Copy)]
+pub struct Language {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    // File extensions that select the language's syntax.
+    extensions: &'static [&'static str],
+    spec: &'static SyntaxSpec<'static>,
+}
+
+impl Language {
+    pub(crate) const fn new(
+        name: &'static str,
+        spec: &'static SyntaxSpec<'static>,
+        extensions: &'static [&'static str],
+        aliases: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            aliases,
+            extensions,
+            spec,
+        }
+    }
+
+    pub fn find(name: &str) -> Option<&'static Language> {
+        let name = name.trim();
+        LANGUAGES.iter().find(|language| {
+            language.name.eq_ignore_ascii_case(name)
+                || language
+                    .extensions
+                    .iter()
+                    .chain(language.aliases)
+                    .any(|alias| alias.eq_ignore_ascii_case(name))
+        })
+    }
+
+    // Language selected by a lowercase file extension.
+    pub(crate) fn for_extension(ext: &str) -> Option<&'static Language> {
+        LANGUAGES
+            .iter()
+            .find(|language| language.extensions.contains(&ext))
+    }
+
+    pub(crate) fn syntax(&self) -> SyntaxInfo {
+        SyntaxInfo {
+            spec: self.spec,
+            language: self.name,
+        }
+    }
+}
+
+impl PartialEq for Language {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Language {}
+
+impl fmt::Debug for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Language")
+            .field("name", &self.name)
+            .field("aliases", &self.aliases)
+            .field("extensions", &self.extensions)
+            .finish_non_exhaustive()
+    }
+}
+
+// Canonical name for a language name or alias (`rs` -> `rust`).
+pub fn canonical_language(name: &str) -> Option<&'static str> {
+    Language::find(name).map(|language| language.name)
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LanguageMapping {
+    pub pattern: String,
+    pub language: String,
+}
+
+// Maps file extensions or globs onto a language name, overriding the built-in
+// table. A bare word (`jsonc`, `.h`) is an extension; anything else is a glob,
+// matched against the file name, or against the tail of the path when it
+// contains a `/`.
+#[derive(Clone, Debug, Default)]
+pub struct LanguageMap {
+    entries: Vec<MapEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct MapEntry {
+    matcher: GlobMatcher,
+    file_name_only: bool,
+    language: String,
+}
+
+impl LanguageMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn insert(&mut self, pattern: &str, language: &str) -> Result<(), globset::Error> {
+        let pattern = pattern.trim();
+        let language = language.trim();
+        if pattern.is_empty() || language.is_empty() {
+            return Ok(());
+        }
+        let is_glob = pattern.contains(['*', '?', '[', '{', '/']);
+        let (matcher, file_name_only) = if is_glob {
+            let file_name_only = !pattern.contains('/');
+            let pattern = pattern.trim_start_matches('/');
+            let pattern = if file_name_only || pattern.starts_with("**/") {
+                pattern.to_string()
+            } else {
+                format!("**/{pattern}")
+            };
+            let glob = GlobBuilder::new(&pattern)
+                .case_insensitive(file_name_only)
+                .literal_separator(true)
+                .build()?;
+            (glob.compile_matcher(), file_name_only)
+        } else {
+            let ext = pattern.trim_start_matches('.').to_ascii_lowercase();
+            let glob = GlobBuilder::new(&format!("*.{ext}"))
+                .case_insensitive(true)
+                .build()?;
+            (glob.compile_matcher(), true)
+        };
+        self.entries.push(MapEntry {
+            matcher,
+            file_name_only,
+            language: language.to_string(),
+        });
+        Ok(())
+    }
+
+    // Earlier mappings win over later ones.
+    pub(crate) fn language_for(&self, path: &Path) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let name = path.file_name()?;
+        self.entries
+            .iter()
+            .find(|entry| {
+                if entry.file_name_only {
+                    entry.matcher.is_match(name)
+                } else {
+                    entry.matcher.is_match(path)
+                }
+            })
+            .map(|entry| entry.language.as_str())
+    }
+}
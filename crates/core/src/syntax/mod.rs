@@ -1,3 +1,17 @@
+mod attributes;
 mod comments;
+mod custom;
+mod detect;
+mod language;
+mod mapping;
 
-pub use comments::{BlockState, SyntaxSpec, find_comment_ranges, syntax_for_path};
+pub(crate) use attributes::GitAttributes;
+pub use comments::{
+    BlockState, CommentRange, SyntaxInfo, SyntaxSpec, find_comment_ranges, syntax_for_path,
+};
+pub(crate) use comments::{long_bracket_open, opens_longer_block};
+pub(crate) use custom::{CustomSyntax, custom_syntax_for_path, custom_syntax_named};
+pub use custom::{LanguageDefinition, StringDefinition};
+pub(crate) use detect::{syntax_for_content, syntax_for_language};
+pub use language::{Language, canonical_language};
+pub use mapping::{LanguageMap, LanguageMapping};
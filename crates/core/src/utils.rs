@@ -1,6 +1,5 @@
 use std::path::PathBuf;
 
-use crate::constants::normalize_mark;
 use crate::model::{DimensionValue, Mark};
 use crate::sort::{DimensionStage, FolderSortConfig};
 
@@ -10,15 +9,47 @@ pub(crate) fn extract_dimension_value(
     roots: &[PathBuf],
 ) -> Option<DimensionValue> {
     match stage {
-        DimensionStage::Mark(_) => {
-            normalize_mark(mark.mark).map(|kind| DimensionValue::Mark(kind.into()))
+        DimensionStage::Mark(_) => Some(DimensionValue::Mark(mark.mark.to_string().into())),
+        DimensionStage::Language(_) => {
+            Some(DimensionValue::Language(mark.language.to_string().into()))
         }
-        DimensionStage::Language(_) => Some(DimensionValue::Language(mark.language.into())),
         DimensionStage::Path(_) => Some(DimensionValue::Path((*mark.path).clone())),
         DimensionStage::Folder(config) => {
             let key = folder_key(mark.path.as_ref(), roots, config);
             Some(DimensionValue::Folder(key))
         }
+        DimensionStage::Owner(_) => mark
+            .assignees
+            .first()
+            .map(|owner| DimensionValue::Owner(owner.clone().into())),
+        DimensionStage::Issue(_) => mark
+            .issues
+            .first()
+            .map(|issue| DimensionValue::Issue(issue.clone().into())),
+        DimensionStage::Due(_) => mark.due.map(|due| DimensionValue::Due(Some(due))),
+        DimensionStage::Comment(_) => Some(DimensionValue::Comment(mark.comment_kind)),
+    }
+}
+
+pub(crate) fn extract_dimension_values(
+    stage: &DimensionStage,
+    mark: &Mark,
+    roots: &[PathBuf],
+) -> Vec<DimensionValue> {
+    match stage {
+        DimensionStage::Owner(_) => mark
+            .assignees
+            .iter()
+            .map(|owner| DimensionValue::Owner(owner.clone().into()))
+            .collect(),
+        DimensionStage::Issue(_) => mark
+            .issues
+            .iter()
+            .map(|issue| DimensionValue::Issue(issue.clone().into()))
+            .collect(),
+        _ => extract_dimension_value(stage, mark, roots)
+            .into_iter()
+            .collect(),
     }
 }
 
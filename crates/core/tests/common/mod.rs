@@ -0,0 +1,57 @@
+// Fixture helpers shared by the per-language scan tests. Each test crate
+// uses a different subset of them.
+#![allow(dead_code)]
+
+use std::error::Error;
+use std::fs;
+
+use doto_core::{Mark, ScanConfig, ScanConfigBuilder, scan};
+use tempfile::TempDir;
+
+// Scans a single file written to a fresh directory with the default config.
+pub fn scan_source(name: &str, contents: &str) -> Result<Vec<Mark>, Box<dyn Error>> {
+    scan_files(&[(name, contents)], |builder| builder)
+}
+
+// Writes each `(path, contents)` pair under a fresh directory, creating
+// parent directories as needed.
+pub fn write_files(files: &[(&str, &str)]) -> Result<TempDir, Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    for (name, contents) in files {
+        let path = temp.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(temp)
+}
+
+// Scans `files` with the builder `configure` returns and sorts the marks by
+// path and position.
+pub fn scan_files(
+    files: &[(&str, &str)],
+    configure: impl FnOnce(ScanConfigBuilder) -> ScanConfigBuilder,
+) -> Result<Vec<Mark>, Box<dyn Error>> {
+    let temp = write_files(files)?;
+    let config = configure(ScanConfig::builder().root(temp.path())).build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    Ok(marks)
+}
+
+// (language, line, mark) for every mark.
+pub fn lines(marks: &[Mark]) -> Vec<(&str, u32, &str)> {
+    marks
+        .iter()
+        .map(|mark| (&*mark.language, mark.line, &*mark.mark))
+        .collect()
+}
+
+// (language, line, message) for every mark.
+pub fn messages(marks: &[Mark]) -> Vec<(&str, u32, &str)> {
+    marks
+        .iter()
+        .map(|mark| (&*mark.language, mark.line, mark.message.as_str()))
+        .collect()
+}
@@ -0,0 +1,221 @@
+use std::error::Error;
+use std::fs;
+
+use doto_core::{
+    DimensionStage, DimensionValue, DueDate, DueSortConfig, FilterConfig, FilterRule,
+    OwnerSortConfig, ScanConfig, ValuePredicate, scan, scan_grouped,
+};
+use tempfile::TempDir;
+
+#[test]
+fn scan_parses_owner_and_issue_annotations() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    let contents = "// TODO(alice): one\n// FIXME(#1234): two\n// TODO(JIRA-88, @bob): three\n";
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| mark.line);
+
+    assert_eq!(marks[0].assignees, vec!["alice"]);
+    assert!(marks[0].issues.is_empty());
+    assert_eq!(marks[0].message, "one");
+    assert!(marks[1].assignees.is_empty());
+    assert_eq!(marks[1].issues, vec!["#1234"]);
+    assert_eq!(marks[2].assignees, vec!["bob"]);
+    assert_eq!(marks[2].issues, vec!["JIRA-88"]);
+    assert_eq!(marks[2].message, "three");
+    Ok(())
+}
+
+#[test]
+fn scan_filters_by_any_owner() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// TODO(alice, bob): one\n// TODO(carol): two\n// TODO: three\n",
+    )?;
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Owner(OwnerSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: vec![DimensionValue::Owner("@Bob".into())],
+            },
+        }],
+    };
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .filter_config(filter)
+        .build();
+    let result = scan(config)?;
+
+    assert_eq!(result.marks.len(), 1);
+    assert_eq!(result.marks[0].line, 1);
+    Ok(())
+}
+
+#[test]
+fn scan_groups_by_owner_with_unassigned_last() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// TODO: none\n// TODO(bob): one\n// FIXME(alice): two\n",
+    )?;
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .sort_pipeline(vec![DimensionStage::Owner(OwnerSortConfig::default())])
+        .build();
+    let result = scan_grouped(config)?;
+
+    let keys = result
+        .tree
+        .groups
+        .iter()
+        .map(|group| group.key.clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        keys,
+        vec![
+            DimensionValue::Owner("alice".into()),
+            DimensionValue::Owner("bob".into()),
+            DimensionValue::Owner("".into()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_groups_by_every_owner() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// TODO(alice, bob): shared\n// TODO(bob): own\n",
+    )?;
+
+    let pipeline = vec![DimensionStage::Owner(OwnerSortConfig::default())];
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .sort_pipeline(pipeline.clone())
+        .build();
+    let result = scan_grouped(config)?;
+
+    let groups = result
+        .tree
+        .groups
+        .iter()
+        .map(|group| (group.key.clone(), group.count))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        groups,
+        vec![
+            (DimensionValue::Owner("alice".into()), 1),
+            (DimensionValue::Owner("bob".into()), 2),
+        ]
+    );
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .sort_pipeline(pipeline)
+        .build();
+    let result = scan(config)?;
+    let lines = result
+        .marks
+        .iter()
+        .map(|mark| mark.line)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![1, 2]);
+    Ok(())
+}
+
+#[test]
+fn scan_parses_due_dates() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    let contents = "// TODO(2026-12-01): drop legacy API\n// TODO(due: 2026-Q4, alice): two\n// TODO(2026-02-30): invalid\n";
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| mark.line);
+
+    assert_eq!(marks[0].due, Some("2026-12-01".parse()?));
+    assert_eq!(marks[0].message, "drop legacy API");
+    assert_eq!(marks[1].due, DueDate::new(2026, 12, 31));
+    assert_eq!(marks[1].assignees, vec!["alice"]);
+    assert_eq!(marks[2].due, None);
+    Ok(())
+}
+
+#[test]
+fn scan_filters_marks_due_before_date() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// TODO(2020-01-01): old\n// TODO(2999-01-01): future\n// TODO: undated\n",
+    )?;
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Due(DueSortConfig::default()),
+            predicate: ValuePredicate::Before {
+                value: DimensionValue::Due(Some(DueDate::today())),
+            },
+        }],
+    };
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .filter_config(filter)
+        .build();
+    let result = scan(config)?;
+
+    assert_eq!(result.marks.len(), 1);
+    assert_eq!(result.marks[0].message, "old");
+    Ok(())
+}
+
+#[test]
+fn scan_filters_due_before_start_of_period() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// TODO(2026-06-30): june\n// TODO(2026-07-01): july\n// TODO(2026-Q3): quarter\n",
+    )?;
+
+    let bound = DueDate::parse_start("2026-Q3")?;
+    assert_eq!(bound, DueDate::new(2026, 7, 1).unwrap());
+    assert_eq!(
+        DueDate::parse_start("2026-02")?,
+        DueDate::new(2026, 2, 1).unwrap()
+    );
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Due(DueSortConfig::default()),
+            predicate: ValuePredicate::Before {
+                value: DimensionValue::Due(Some(bound)),
+            },
+        }],
+    };
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .filter_config(filter)
+        .build();
+    let result = scan(config)?;
+
+    let messages = result
+        .marks
+        .iter()
+        .map(|mark| mark.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec!["june"]);
+    Ok(())
+}
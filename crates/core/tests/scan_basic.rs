@@ -19,9 +19,9 @@ fn scan_finds_builtin_marks() -> Result<(), Box<dyn Error>> {
     let mut marks = result
         .marks
         .iter()
-        .map(|mark| (mark.path.as_ref().clone(), mark.line, mark.mark))
+        .map(|mark| (mark.path.as_ref().clone(), mark.line, mark.mark.as_ref()))
         .collect::<Vec<_>>();
-    marks.sort_by(|a, b| a.1.cmp(&b.1));
+    marks.sort_by_key(|mark| mark.1);
 
     assert_eq!(marks.len(), 2);
     assert_eq!(marks[0].0, file_path);
@@ -32,3 +32,27 @@ fn scan_finds_builtin_marks() -> Result<(), Box<dyn Error>> {
     assert_eq!(marks[1].2, "FIXME");
     Ok(())
 }
+
+#[test]
+fn scan_captures_mark_message() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("main.rs");
+    let contents = "// TODO: fix this\n/* FIXME(alice): leak */\n//! NOTE plain text\n// TODO\n";
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let result = scan(config)?;
+
+    let mut marks = result
+        .marks
+        .iter()
+        .map(|mark| (mark.line, mark.message.as_str()))
+        .collect::<Vec<_>>();
+    marks.sort_by_key(|mark| mark.0);
+
+    assert_eq!(
+        marks,
+        vec![(1, "fix this"), (2, "leak"), (3, "plain text"), (4, "")]
+    );
+    Ok(())
+}
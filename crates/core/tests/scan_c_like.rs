@@ -0,0 +1,215 @@
+use std::error::Error;
+
+mod common;
+
+use common::{lines, scan_files, scan_source};
+use doto_core::CommentKind;
+
+#[test]
+fn scan_tracks_nested_rust_block_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "lib.rs",
+        "/* outer /* inner */ still \"comment\n   TODO: nested */\nlet s = \"// TODO: string\";\n// FIXME: after\n",
+    )?;
+
+    assert_eq!(lines(&marks), [("rust", 2, "TODO"), ("rust", 4, "FIXME")]);
+    assert_eq!(
+        marks
+            .iter()
+            .map(|mark| mark.comment_kind)
+            .collect::<Vec<_>>(),
+        [CommentKind::Block, CommentKind::Line]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_rust_raw_strings() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "raw.rs",
+        r##"let a = br"// WARN: bytes"; // TODO: rust
+let b = r#"/* WARN: "hashes" */"#; // FIXME: hashes
+"##,
+    )?;
+
+    assert_eq!(lines(&marks), [("rust", 1, "TODO"), ("rust", 2, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_cpp_raw_strings() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "raw.cpp",
+        r#"auto a = R"x(// WARN: raw)x"; // TODO: cpp
+auto b = u8R"(/* WARN: open
+still raw */ )"; // FIXME: multiline raw
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("cpp", 1, "TODO"), ("cpp", 3, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_csharp_verbatim_and_raw_strings() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "Paths.cs",
+        r#"var a = @"C:\dir\"; // TODO: verbatim
+var b = @"say ""hi"" // WARN: quoted"; // FIXME: doubled quotes
+var c = """
+    // WARN: raw literal
+    """; // NOTE: after raw
+"#,
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [
+            ("csharp", 1, "TODO"),
+            ("csharp", 2, "FIXME"),
+            ("csharp", 5, "NOTE"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_java_text_blocks() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "Block.java",
+        r#"String a = """
+    // WARN: text block
+    """; // TODO: java
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("java", 3, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_kotlin_raw_strings() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "Raw.kt",
+        r#"val a = """C:\"""; // TODO: kotlin
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("kotlin", 1, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_preprocessor_disabled_code() -> Result<(), Box<dyn Error>> {
+    let contents = r#"#include <sys/*.h>
+#if 0 // NOTE: kept for reference
+// TODO: dead
+#ifdef DEBUG
+// FIXME: still dead
+#endif
+#else
+// ERROR: live
+#endif
+#if defined(FEATURE)
+// WARN: depends on the build
+#elif 1
+#else
+// NOTE: dead once a branch is known
+#endif
+#error don't build this // INFO: after the message
+"#;
+    let scan_marks = |disabled_code: bool| -> Result<Vec<_>, Box<dyn Error>> {
+        let marks = scan_files(&[("legacy.cpp", contents)], |builder| {
+            builder.disabled_code(disabled_code)
+        })?;
+        Ok(marks
+            .iter()
+            .map(|mark| (mark.line, mark.mark.to_string(), mark.disabled))
+            .collect::<Vec<_>>())
+    };
+
+    assert_eq!(
+        scan_marks(false)?,
+        vec![
+            (2, "NOTE".to_string(), false),
+            (8, "ERROR".to_string(), false),
+            (11, "WARN".to_string(), false),
+            (16, "INFO".to_string(), false),
+        ]
+    );
+    assert_eq!(
+        scan_marks(true)?,
+        vec![
+            (2, "NOTE".to_string(), false),
+            (3, "TODO".to_string(), true),
+            (5, "FIXME".to_string(), true),
+            (8, "ERROR".to_string(), false),
+            (11, "WARN".to_string(), false),
+            (14, "NOTE".to_string(), true),
+            (16, "INFO".to_string(), false),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_comments_after_javascript_regexes() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "app.js",
+        r#"const re = /\/\*/; // TODO: after regex
+const q = /"/.test(s); // FIXME: quote in regex
+const r = total / 2 / count; // NOTE: division
+if (ok) return /[/]/.source; // TODO: slash in class
+const m = s.replace(
+  /\/\/ x/g, "y"); // FIXME: regex on its own line
+"#,
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [
+            ("javascript", 1, "TODO"),
+            ("javascript", 2, "FIXME"),
+            ("javascript", 3, "NOTE"),
+            ("javascript", 4, "TODO"),
+            ("javascript", 6, "FIXME"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_comments_in_javascript_template_interpolations() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "app.js",
+        r#"const t = `a ${ /* WARN: inside interpolation */ x } b // not a comment`; // ERROR: after template
+const u = `${ `nested ${y}` } // still string`; // INFO: nested template
+const v = `${
+  // NOTE: multiline interpolation
+  value
+}`;
+"#,
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [
+            ("javascript", 1, "WARN"),
+            ("javascript", 1, "ERROR"),
+            ("javascript", 2, "INFO"),
+            ("javascript", 4, "NOTE"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_comments_after_jsx_closing_tags() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "app.js",
+        "const A = () => <div>hi</div>; // TODO: after jsx\n",
+    )?;
+
+    assert_eq!(lines(&marks), [("javascript", 1, "TODO")]);
+    Ok(())
+}
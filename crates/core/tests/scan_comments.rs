@@ -1,7 +1,10 @@
 use std::error::Error;
 use std::fs;
 
-use doto_core::{ScanConfig, scan};
+use doto_core::{
+    CommentKind, CommentSortConfig, DimensionStage, DimensionValue, FilterConfig, FilterRule,
+    MarkPosition, ScanConfig, ValuePredicate, scan,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -26,7 +29,7 @@ fn main() {
     let mut marks = result
         .marks
         .iter()
-        .map(|mark| (mark.line, mark.mark))
+        .map(|mark| (mark.line, mark.mark.as_ref()))
         .collect::<Vec<_>>();
     marks.sort();
 
@@ -52,7 +55,7 @@ const note = `// TODO: not a comment`;
     let mut marks = result
         .marks
         .iter()
-        .map(|mark| (mark.line, mark.mark))
+        .map(|mark| (mark.line, mark.mark.as_ref()))
         .collect::<Vec<_>>();
     marks.sort();
 
@@ -96,3 +99,212 @@ const note = `// TODO: not a comment`
     assert_eq!(result.stats.matches, 1);
     Ok(())
 }
+
+#[test]
+fn scan_joins_comment_continuation_lines() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    let contents = r#"
+// TODO: first line
+// second line
+//
+// unrelated
+fn main() {
+    /*
+     * FIXME: block start
+     * block end
+     */
+    // NOTE: note
+    let x = 1; // trailing
+}
+"#;
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| mark.line);
+
+    let marks = marks
+        .iter()
+        .map(|mark| (mark.line, mark.end_line, mark.message.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        marks,
+        vec![
+            (2, 3, "first line second line"),
+            (8, 9, "block start block end"),
+            (11, 11, "note"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_keeps_single_line_marks_when_multiline_disabled() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("main.py");
+    fs::write(&file_path, "# TODO: first\n# second\n")?;
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .multiline_marks(false)
+        .build();
+    let result = scan(config)?;
+
+    assert_eq!(result.marks.len(), 1);
+    assert_eq!(result.marks[0].end_line, 1);
+    assert_eq!(result.marks[0].message, "first");
+    Ok(())
+}
+
+#[test]
+fn scan_finds_marks_anywhere_in_comments() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    let contents = "// see parser above, TODO handle EOF\n/* TODO: one, FIXME: two */\n// TODOS and XTODO are words\n";
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let result = scan(config)?;
+    assert_eq!(result.stats.matches, 1);
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .mark_position(MarkPosition::Anywhere)
+        .build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| (mark.line, mark.column));
+
+    let marks = marks
+        .iter()
+        .map(|mark| {
+            (
+                mark.line,
+                mark.column,
+                mark.mark.as_ref(),
+                mark.message.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        marks,
+        vec![
+            (1, 22, "TODO", "handle EOF"),
+            (2, 4, "TODO", "one,"),
+            (2, 15, "FIXME", "two"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_classifies_comment_kinds() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    let contents = r#"//! TODO: crate docs
+/// FIXME: item docs
+//// NOTE: plain line
+/** TODO: block docs */
+/* WARN: plain block */
+// INFO: line
+"#;
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| mark.line);
+
+    let kinds = marks
+        .iter()
+        .map(|mark| mark.comment_kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            CommentKind::DocLine,
+            CommentKind::DocLine,
+            CommentKind::Line,
+            CommentKind::DocBlock,
+            CommentKind::Block,
+            CommentKind::Line,
+        ]
+    );
+    assert_eq!(marks[3].message, "block docs");
+    Ok(())
+}
+
+#[test]
+fn scan_detects_marks_in_python_docstrings() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("main.py");
+    let contents = r#"#!/usr/bin/env python3
+"""INFO: module docstring"""
+import os
+
+def run():
+    """TODO: document the return value"""
+    value = """FIXME: not a docstring"""
+    """
+    NOTE: not a docstring either
+    """
+    # WARN: comment
+    foo(
+        """TODO: call argument"""
+    )
+
+class Widget(
+    Base,
+):
+    # comments may come first
+    """
+    NOTE: spans
+    several lines
+    """
+"#;
+    fs::write(&file_path, contents)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| mark.line);
+
+    let found = marks
+        .iter()
+        .map(|mark| (mark.line, mark.comment_kind, mark.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (2, CommentKind::Docstring, "module docstring"),
+            (6, CommentKind::Docstring, "document the return value"),
+            (11, CommentKind::Line, "comment"),
+            (21, CommentKind::Docstring, "spans several lines"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_filters_by_comment_kind() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(&file_path, "/// TODO: public\n// TODO: internal\n")?;
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Comment(CommentSortConfig::default()),
+            predicate: ValuePredicate::Deny {
+                values: vec![DimensionValue::Comment(CommentKind::DocLine)],
+            },
+        }],
+    };
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .filter_config(filter)
+        .build();
+    let result = scan(config)?;
+
+    assert_eq!(result.marks.len(), 1);
+    assert_eq!(result.marks[0].message, "internal");
+    Ok(())
+}
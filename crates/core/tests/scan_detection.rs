@@ -0,0 +1,142 @@
+use std::error::Error;
+
+mod common;
+
+use common::{lines, scan_files, write_files};
+use doto_core::{LanguageDefinition, LanguageMap, ScanConfig, StringDefinition, scan};
+
+#[test]
+fn scan_uses_declared_languages() -> Result<(), Box<dyn Error>> {
+    let files = [
+        (
+            "default.nix",
+            "{ x = \"# NOTE: string\"; } # TODO: pin\n/* FIXME: block */\n",
+        ),
+        ("BUILD", "rule(name = 'a') ;; WARN: custom token\n"),
+        ("main.rs", "// TODO: builtin override\n"),
+    ];
+    let marks = scan_files(&files, |builder| {
+        builder
+            .language(LanguageDefinition {
+                name: "nix".to_string(),
+                extensions: vec![".nix".to_string()],
+                line_comment: Some("#".to_string()),
+                block_comment: Some(("/*".to_string(), "*/".to_string())),
+                strings: vec![StringDefinition {
+                    token: "\"".to_string(),
+                    ..StringDefinition::default()
+                }],
+                ..LanguageDefinition::default()
+            })
+            .language(LanguageDefinition {
+                name: "starlark".to_string(),
+                filenames: vec!["BUILD".to_string()],
+                line_comment: Some(";;".to_string()),
+                ..LanguageDefinition::default()
+            })
+            .language(LanguageDefinition {
+                name: "rusty".to_string(),
+                extensions: vec!["rs".to_string()],
+                line_comment: Some("#".to_string()),
+                ..LanguageDefinition::default()
+            })
+    })?;
+
+    assert_eq!(
+        lines(&marks),
+        [
+            ("starlark", 1, "WARN"),
+            ("nix", 1, "TODO"),
+            ("nix", 2, "FIXME"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_detects_extensionless_files() -> Result<(), Box<dyn Error>> {
+    let files = [
+        (
+            "bin/deploy",
+            "#!/usr/bin/env python3\nx = '# NOTE: string'  # TODO: deploy\n",
+        ),
+        (
+            "bin/setup",
+            "# -*- mode: sh -*-\necho hi # FIXME: quoting\n",
+        ),
+        ("bin/notes", "plain text # TODO: not code\n"),
+        // Unknown extensions are skipped without looking inside.
+        ("bin/deploy.dat", "#!/bin/sh\n# TODO: not probed\n"),
+        ("Jenkinsfile", "// TODO: pipeline\n"),
+        ("CMakeLists.txt", "# WARN: cmake\n"),
+        ("api.Dockerfile", "# INFO: image\n"),
+    ];
+    let marks = scan_files(&files, |builder| builder)?;
+
+    assert_eq!(
+        lines(&marks),
+        [
+            ("cmake", 1, "WARN"),
+            ("groovy", 1, "TODO"),
+            ("dockerfile", 1, "INFO"),
+            ("python", 2, "TODO"),
+            ("shell", 2, "FIXME"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_applies_language_mappings() -> Result<(), Box<dyn Error>> {
+    let files = [
+        ("settings.jsonc", "{ // TODO: schema\n}\n"),
+        ("api.h", "// FIXME: header\n"),
+        ("views/page.tpl", "<!-- NOTE: template -->\n"),
+        ("views/legacy/old.tpl", "<!-- NOTE: unset -->\n"),
+        (
+            ".gitattributes",
+            "*.tpl linguist-language=HTML\nviews/legacy/** -linguist-language\n",
+        ),
+    ];
+    let mut language_map = LanguageMap::new();
+    language_map.insert("jsonc", "c")?;
+    language_map.insert(".h", "c++")?;
+    let marks = scan_files(&files, |builder| builder.language_map(language_map))?;
+
+    assert_eq!(
+        lines(&marks),
+        [("cpp", 1, "FIXME"), ("c", 1, "TODO"), ("html", 1, "NOTE")]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_nested_gitattributes() -> Result<(), Box<dyn Error>> {
+    let temp = write_files(&[
+        (".gitattributes", "*.tpl linguist-language=HTML\n"),
+        (
+            "views/.gitattributes",
+            "legacy.tpl linguist-language=shell\n",
+        ),
+        ("views/page.tpl", "<!-- NOTE: template -->\n"),
+        ("views/legacy.tpl", "# TODO: legacy\n"),
+        ("api/.gitattributes", "gen/** linguist-generated\n"),
+        ("api/gen/client.rs", "// FIXME: generated\n"),
+        ("api/lib.rs", "// WARN: handwritten\n"),
+    ])?;
+
+    let result = scan(ScanConfig::builder().root(temp.path()).build())?;
+    let mut marks = result
+        .marks
+        .iter()
+        .map(|mark| (&*mark.language, &*mark.mark))
+        .collect::<Vec<_>>();
+    marks.sort();
+
+    assert_eq!(
+        marks,
+        [("html", "NOTE"), ("rust", "WARN"), ("shell", "TODO")]
+    );
+    assert_eq!(result.stats.skips.generated, 1);
+    Ok(())
+}
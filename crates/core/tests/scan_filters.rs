@@ -1,7 +1,10 @@
 use std::error::Error;
 use std::fs;
 
-use doto_core::{ScanConfig, scan};
+use doto_core::{
+    DimensionStage, DimensionValue, FilterConfig, FilterRule, LanguageSortConfig, ScanConfig,
+    ValuePredicate, scan,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -128,6 +131,123 @@ fn scan_skips_binary_files() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn scan_counts_extensionless_binaries_as_binary() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    fs::create_dir(temp.path().join("bin"))?;
+    fs::write(temp.path().join("bin/tool"), b"\x7fELF\x02\x01\0\0TODO\0")?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let result = scan(config)?;
+
+    assert_eq!(result.stats.skips.binary, 1);
+    assert_eq!(result.stats.skips.unsupported_syntax, 0);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_generated_and_vendored_files() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    fs::write(
+        temp.path().join("api.pb.go"),
+        "// Code generated by protoc-gen-go. DO NOT EDIT.\n// TODO: generated\n",
+    )?;
+    fs::write(
+        temp.path().join("bindings.rs"),
+        "/* automatically generated by rust-bindgen */\n// TODO: bindgen\n",
+    )?;
+    fs::write(temp.path().join("schema.rs"), "// TODO: schema\n")?;
+    fs::write(temp.path().join("manual.rs"), "// TODO: manual\n")?;
+    fs::create_dir(temp.path().join("third_party"))?;
+    fs::write(
+        temp.path().join("third_party/lib.rs"),
+        "// TODO: vendored\n",
+    )?;
+    fs::write(
+        temp.path().join(".gitattributes"),
+        "*.rs linguist-generated\nmanual.rs -linguist-generated\nthird_party/** linguist-vendored\nschema.rs linguist-generated=false\n",
+    )?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let result = scan(config)?;
+    let mut messages = result
+        .marks
+        .iter()
+        .map(|mark| mark.message.clone())
+        .collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, vec!["manual", "schema"]);
+    assert_eq!(result.stats.skips.generated, 3);
+    assert_eq!(result.stats.files_skipped, 3);
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .include_generated(true)
+        .build();
+    let result = scan(config)?;
+    assert_eq!(result.stats.matches, 5);
+    assert_eq!(result.stats.skips.generated, 0);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_generated_headers_without_attributes() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    fs::write(
+        temp.path().join("b.rs"),
+        "/* automatically generated by rust-bindgen 0.69.4 */\n// TODO: bindgen\n",
+    )?;
+    fs::write(
+        temp.path().join("g.py"),
+        "# Code generated by tool. DO NOT EDIT.\n# TODO: python\n",
+    )?;
+    fs::write(
+        temp.path().join("api.pb.go"),
+        "// Code generated by protoc-gen-go. DO NOT EDIT.\n// TODO: go\n",
+    )?;
+    fs::write(temp.path().join("main.rs"), "// TODO: manual\n")?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let result = scan(config)?;
+    let messages = result
+        .marks
+        .iter()
+        .map(|mark| mark.message.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec!["manual"]);
+    assert_eq!(result.stats.skips.generated, 3);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_files_that_only_mention_generated_markers() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    fs::write(
+        temp.path().join("keys.go"),
+        "// Config keys. DO NOT EDIT without updating docs.\n// TODO: keys\n",
+    )?;
+    fs::write(
+        temp.path().join("header.rs"),
+        "const HEADER: &str = \"// @generated by mytool\";\n// TODO: header\n",
+    )?;
+    fs::write(
+        temp.path().join("Model.cs"),
+        "// <auto-generated>\n// TODO: generated\n",
+    )?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let result = scan(config)?;
+    let mut messages = result
+        .marks
+        .iter()
+        .map(|mark| mark.message.clone())
+        .collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, vec!["header", "keys"]);
+    assert_eq!(result.stats.skips.generated, 1);
+    Ok(())
+}
+
 #[test]
 #[cfg(unix)]
 fn scan_ignores_symlinked_files() -> Result<(), Box<dyn Error>> {
@@ -153,3 +273,36 @@ fn scan_ignores_symlinked_files() -> Result<(), Box<dyn Error>> {
     assert_eq!(result.marks[0].path.as_ref(), &target_path);
     Ok(())
 }
+
+#[test]
+fn scan_filters_languages_by_alias() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    fs::write(temp.path().join("lib.rs"), "// TODO: rust\n")?;
+    fs::write(temp.path().join("app.py"), "# TODO: python\n")?;
+    fs::write(temp.path().join("main.ts"), "// TODO: typescript\n")?;
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Language(LanguageSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: vec![
+                    DimensionValue::Language("rs".into()),
+                    DimensionValue::Language("TSX".into()),
+                ],
+            },
+        }],
+    };
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .filter_config(filter)
+        .build();
+    let mut languages = scan(config)?
+        .marks
+        .into_iter()
+        .map(|mark| mark.language.to_string())
+        .collect::<Vec<_>>();
+    languages.sort();
+
+    assert_eq!(languages, vec!["rust", "typescript"]);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::error::Error;
+
+mod common;
+
+use common::{lines, messages, scan_source};
+use doto_core::CommentKind;
+
+#[test]
+fn scan_tracks_nested_haskell_block_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "Main.hs",
+        "{- outer {- inner -} -}\n-- | TODO: haddock\nmain = putStrLn \"-- NOTE: string\"\n{- FIXME: {- nested -} block -}\n",
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [("haskell", 2, "TODO"), ("haskell", 4, "FIXME")]
+    );
+    assert_eq!(
+        marks
+            .iter()
+            .map(|mark| mark.comment_kind)
+            .collect::<Vec<_>>(),
+        [CommentKind::DocLine, CommentKind::Block]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_haskell_char_literals() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "Quote.hs",
+        "isQuote c = c == '\"' -- TODO: after char\nx' = x'' + 1 -- FIXME: primes\nq = '\\'' -- NOTE: escaped quote\n",
+    )?;
+
+    assert_eq!(
+        messages(&marks),
+        [
+            ("haskell", 1, "after char"),
+            ("haskell", 2, "primes"),
+            ("haskell", 3, "escaped quote"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_tracks_nested_ocaml_block_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "main.ml",
+        "(* TODO: outer (* inner *) tail *)\nlet s = \"(* NOTE: string *)\"\n(** FIXME: doc *)\n",
+    )?;
+
+    assert_eq!(lines(&marks), [("ocaml", 1, "TODO"), ("ocaml", 3, "FIXME")]);
+    assert_eq!(
+        marks
+            .iter()
+            .map(|mark| mark.comment_kind)
+            .collect::<Vec<_>>(),
+        [CommentKind::Block, CommentKind::DocBlock]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_ocaml_char_literals() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "quote.ml",
+        "let q = '\"' (* TODO: after char *)\nlet f (x : 'a list) = x (* FIXME: type variable *)\nlet b = '\\\\' (* NOTE: backslash *)\n",
+    )?;
+
+    assert_eq!(
+        messages(&marks),
+        [
+            ("ocaml", 1, "after char"),
+            ("ocaml", 2, "type variable"),
+            ("ocaml", 3, "backslash"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_clojure_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("core.clj", "(def s \"; WARN: string\") ;; TODO: clojure\n")?;
+
+    assert_eq!(lines(&marks), [("clojure", 1, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_lisp_block_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("init.lisp", "#| NOTE: block |#\n;;; FIXME: heading\n")?;
+
+    assert_eq!(lines(&marks), [("lisp", 1, "NOTE"), ("lisp", 2, "FIXME")]);
+    Ok(())
+}
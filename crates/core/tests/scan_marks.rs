@@ -0,0 +1,243 @@
+use std::error::Error;
+use std::fs;
+
+use doto_core::{
+    DetectionMode, DimensionStage, DimensionValue, FilterConfig, FilterRule, MarkRegistry,
+    MarkSortConfig, ScanConfig, ValuePredicate, scan,
+};
+use tempfile::TempDir;
+
+#[test]
+fn scan_detects_custom_marks() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// HACK: one\n// SAFETY: two\n// TODO: three\n// XXX: not registered\n",
+    )?;
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .add_mark("HACK", 1)
+        .add_mark("safety", 4)
+        .build();
+    let result = scan(config)?;
+
+    let mut marks = result
+        .marks
+        .iter()
+        .map(|mark| (mark.line, mark.mark.as_ref()))
+        .collect::<Vec<_>>();
+    marks.sort();
+
+    assert_eq!(marks, vec![(1, "HACK"), (2, "SAFETY"), (3, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_removed_marks() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(&file_path, "// INFO: one\n// NOTE: two\n")?;
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .remove_mark("info")
+        .build();
+    let result = scan(config)?;
+
+    assert_eq!(result.stats.matches, 1);
+    assert_eq!(result.marks[0].mark.as_ref(), "NOTE");
+    Ok(())
+}
+
+#[test]
+fn scan_sorts_by_registry_priority() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(&file_path, "// TODO: one\n// PERF: two\n// FIXME: three\n")?;
+
+    let mut marks = MarkRegistry::builtin();
+    marks.insert("PERF", 0);
+    marks.insert("TODO", 1);
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .marks(marks)
+        .sort_pipeline(vec![DimensionStage::Mark(MarkSortConfig::default())])
+        .build();
+    let result = scan(config)?;
+
+    let ordered = result
+        .marks
+        .iter()
+        .map(|mark| mark.mark.as_ref())
+        .collect::<Vec<_>>();
+
+    assert_eq!(ordered, vec!["PERF", "FIXME", "TODO"]);
+    Ok(())
+}
+
+#[test]
+fn scan_respects_detection_mode() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("main.py");
+    fs::write(
+        &file_path,
+        "# todo: lower\n# Fixme(bob): mixed\n# note that this is prose\n# TODO upper\n",
+    )?;
+
+    let lines = |mode| -> Result<Vec<(u32, String)>, Box<dyn Error>> {
+        let config = ScanConfig::builder()
+            .root(temp.path())
+            .detection_mode(mode)
+            .build();
+        let mut marks = scan(config)?
+            .marks
+            .into_iter()
+            .map(|mark| (mark.line, mark.mark.to_string()))
+            .collect::<Vec<_>>();
+        marks.sort();
+        Ok(marks)
+    };
+
+    assert_eq!(lines(DetectionMode::Uppercase)?, vec![(4, "TODO".into())]);
+    assert_eq!(
+        lines(DetectionMode::UppercaseOrColon)?,
+        vec![(1, "TODO".into()), (2, "FIXME".into()), (4, "TODO".into())]
+    );
+    assert_eq!(lines(DetectionMode::CaseInsensitive)?.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn scan_maps_aliases_onto_canonical_marks() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.rs");
+    fs::write(
+        &file_path,
+        "// XXX: one\n// HACK: two\n// FIXME: three\n// TODO: four\n",
+    )?;
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Mark(MarkSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: vec![DimensionValue::Mark("hack".into())],
+            },
+        }],
+    };
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .add_alias("XXX", "FIXME")
+        .add_alias("hack", "fixme")
+        .filter_config(filter)
+        .build();
+    let mut marks = scan(config)?.marks;
+    marks.sort_by_key(|mark| mark.line);
+
+    let found = marks
+        .iter()
+        .map(|mark| (mark.mark.as_ref(), mark.raw_mark.as_ref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![("FIXME", "XXX"), ("FIXME", "HACK"), ("FIXME", "FIXME")]
+    );
+    Ok(())
+}
+
+#[test]
+fn filter_config_resolves_aliases_through_registry() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    fs::write(temp.path().join("lib.rs"), "// XXX: one\n// TODO: two\n")?;
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .add_alias("XXX", "FIXME")
+        .build();
+    let marks = scan(config)?.marks;
+
+    let filter = FilterConfig {
+        rules: vec![FilterRule {
+            stage: DimensionStage::Mark(MarkSortConfig::default()),
+            predicate: ValuePredicate::Allow {
+                values: vec![DimensionValue::Mark("XXX".into())],
+            },
+        }],
+    };
+    let mut registry = MarkRegistry::builtin();
+    registry.add_alias("XXX", "FIXME");
+    let kept = filter.apply(marks, &[temp.path().to_path_buf()], &registry);
+
+    let found = kept
+        .iter()
+        .map(|mark| mark.mark.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec!["FIXME"]);
+    Ok(())
+}
+
+#[test]
+fn registry_resolves_aliases() {
+    let mut marks = MarkRegistry::builtin();
+    assert!(marks.add_alias("BUG", "ERROR"));
+    assert!(!marks.add_alias("OOPS", "MISSING"));
+
+    assert_eq!(
+        marks.normalize("bug").map(|name| name.as_ref()),
+        Some("ERROR")
+    );
+    assert_eq!(marks.priority("BUG"), marks.priority("ERROR"));
+
+    assert!(marks.add_alias("NOTE", "INFO"));
+    assert!(!marks.names().any(|name| name == "NOTE"));
+    assert_eq!(
+        marks.normalize("NOTE").map(|name| name.as_ref()),
+        Some("INFO")
+    );
+
+    marks.remove("ERROR");
+    assert!(!marks.contains("BUG"));
+}
+
+#[test]
+fn scan_detects_pattern_marks() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let file_path = temp.path().join("lib.js");
+    let contents = r#"// @deprecated(since=2.1) use render instead
+const flag = "NOCOMMIT";
+// NOCOMMIT
+// TODO[perf]: cache this
+"#;
+    fs::write(&file_path, contents)?;
+
+    let mut marks = MarkRegistry::builtin();
+    marks.add_pattern("deprecated", r"@deprecated\(since=(?P<since>[^)]*)\)", 3)?;
+    marks.add_pattern("NOCOMMIT", "NOCOMMIT", 0)?;
+    marks.add_pattern("TODO", r"TODO\[(?P<tag>\w+)\]", 5)?;
+    // A pattern for an existing mark keeps that mark's priority.
+    assert_eq!(marks.priority("TODO"), Some(3));
+    assert_eq!(marks.priority("NOCOMMIT"), Some(0));
+
+    let config = ScanConfig::builder().root(temp.path()).marks(marks).build();
+    let mut found = scan(config)?.marks;
+    found.sort_by_key(|mark| mark.line);
+
+    assert_eq!(found.len(), 3);
+    assert_eq!(found[0].mark.as_ref(), "DEPRECATED");
+    assert_eq!(
+        found[0].metadata.get("since").map(String::as_str),
+        Some("2.1")
+    );
+    assert_eq!(found[0].message, "use render instead");
+    assert_eq!(found[1].line, 3);
+    assert_eq!(found[1].mark.as_ref(), "NOCOMMIT");
+    assert_eq!(found[2].mark.as_ref(), "TODO");
+    assert_eq!(found[2].raw_mark.as_ref(), "TODO[perf]");
+    assert_eq!(
+        found[2].metadata.get("tag").map(String::as_str),
+        Some("perf")
+    );
+    assert_eq!(found[2].message, "cache this");
+    Ok(())
+}
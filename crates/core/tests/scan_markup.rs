@@ -0,0 +1,206 @@
+use std::error::Error;
+
+mod common;
+
+use common::{messages, scan_files, scan_source};
+use doto_core::Mark;
+
+#[test]
+fn scan_detects_html_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "index.html",
+        r#"<!-- TODO: add a title -->
+<p title="<!-- TODO: attribute -->">It's <!-- FIXME: inline --> here</p>
+<div
+  data-note='<!-- NOTE: multi-line attribute -->'
+  class="x">
+<!--
+  WARN: spans
+  two lines
+-->
+"#,
+    )?;
+
+    assert_eq!(
+        messages(&marks),
+        [
+            ("html", 1, "add a title"),
+            ("html", 2, "inline"),
+            ("html", 7, "spans two lines"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_xml_cdata_sections() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "feed.xml",
+        "<data><![CDATA[\n<!-- TODO: inside cdata -->\n]]></data>\n<!-- NOTE: after cdata -->\n",
+    )?;
+
+    assert_eq!(messages(&marks), [("xml", 4, "after cdata")]);
+    Ok(())
+}
+
+#[test]
+fn scan_detects_markdown_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("README.md", "# Title\n\n<!-- TODO: write docs -->\n")?;
+
+    assert_eq!(messages(&marks), [("markdown", 3, "write docs")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_markdown_comments_after_prose_brackets() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "a.md",
+        "Compare a<b and it's fine.\n\n<!-- TODO: after comparison -->\nIf x <y and it's true\n<!-- NOTE: after quote -->\n",
+    )?;
+
+    assert_eq!(
+        messages(&marks),
+        [
+            ("markdown", 3, "after comparison"),
+            ("markdown", 5, "after quote"),
+        ]
+    );
+    Ok(())
+}
+
+// (line, embedded language, message) for every mark.
+fn regions(marks: &[Mark]) -> Vec<(u32, Option<&str>, &str)> {
+    marks
+        .iter()
+        .map(|mark| (mark.line, mark.embedded_language, mark.message.as_str()))
+        .collect()
+}
+
+#[test]
+fn scan_switches_syntax_in_vue_blocks() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "App.vue",
+        r#"<template>
+  <!-- TODO: template -->
+  <p>// NOTE: not a comment</p>
+</template>
+<script lang="ts">
+const url = "<!-- FIXME: in a string -->";
+// FIXME: script
+</script>
+<style lang="scss">
+// WARN: style
+</style>
+"#,
+    )?;
+
+    assert!(marks.iter().all(|mark| &*mark.language == "vue"));
+    assert_eq!(
+        regions(&marks),
+        [
+            (2, None, "template"),
+            (7, Some("typescript"), "script"),
+            (10, Some("scss"), "style"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_switches_syntax_in_php_blocks() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "index.php",
+        "<p>// INFO: text</p>\n<?php /* TODO: php */ ?>\n<!-- NOTE: html -->\n",
+    )?;
+
+    assert!(marks.iter().all(|mark| &*mark.language == "php"));
+    assert_eq!(
+        regions(&marks),
+        [(2, Some("php"), "php"), (3, None, "html")]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_markdown_front_matter() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "post.md",
+        "---\n# TODO: front matter\ntitle: x\n---\n# NOTE: heading\n",
+    )?;
+
+    assert!(marks.iter().all(|mark| &*mark.language == "markdown"));
+    assert_eq!(regions(&marks), [(2, Some("yaml"), "front matter")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_astro_frontmatter_and_scripts() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "Page.astro",
+        "---\n// TODO: frontmatter\nconst x = 1;\n---\n<script>/* FIXME: inline */</script>\n",
+    )?;
+
+    assert!(marks.iter().all(|mark| &*mark.language == "astro"));
+    assert_eq!(
+        regions(&marks),
+        [
+            (2, Some("typescript"), "frontmatter"),
+            (5, Some("javascript"), "inline"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_markdown_code_fences() -> Result<(), Box<dyn Error>> {
+    let contents = r#"# Design
+
+- [ ] write the migration
+- [x] agree on naming
+
+```rust
+// TODO: handle errors
+let s = "// NOTE: string";
+```
+
+~~~~python
+# FIXME: slow
+```
+- [ ] not a task
+~~~~
+
+```text
+<!-- WARN: not a comment here -->
+```
+
+<!-- INFO: after blocks -->
+"#;
+    let scan_marks = |task_marks: bool| -> Result<Vec<_>, Box<dyn Error>> {
+        let marks = scan_files(&[("DESIGN.md", contents)], |builder| {
+            builder.task_marks(task_marks)
+        })?;
+        Ok(marks
+            .iter()
+            .map(|mark| (mark.line, mark.mark.to_string(), mark.embedded_language))
+            .collect::<Vec<_>>())
+    };
+
+    assert_eq!(
+        scan_marks(false)?,
+        vec![
+            (7, "TODO".to_string(), Some("rust")),
+            (12, "FIXME".to_string(), Some("python")),
+            (21, "INFO".to_string(), None),
+        ]
+    );
+    assert_eq!(
+        scan_marks(true)?,
+        vec![
+            (3, "TODO".to_string(), None),
+            (7, "TODO".to_string(), Some("rust")),
+            (12, "FIXME".to_string(), Some("python")),
+            (21, "INFO".to_string(), None),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use std::error::Error;
+use std::fs;
+
+use doto_core::{ScanConfig, ScanResult, scan};
+use tempfile::TempDir;
+
+// Writes a Python notebook whose cells sit one per line from line 3 on.
+fn write_notebook(temp: &TempDir, name: &str, cells: &[&str]) -> Result<(), Box<dyn Error>> {
+    let contents = format!(
+        "{{\n \"cells\": [\n{}\n ],\n \"metadata\": {{\"kernelspec\": {{\"language\": \"python\"}}}}\n}}\n",
+        cells.join(",\n")
+    );
+    fs::write(temp.path().join(name), contents)?;
+    Ok(())
+}
+
+fn scan_dir(temp: &TempDir) -> Result<ScanResult, Box<dyn Error>> {
+    Ok(scan(ScanConfig::builder().root(temp.path()).build())?)
+}
+
+// (file line, column, mark) for every mark found.
+fn positions(result: &ScanResult) -> Vec<(u32, u32, String)> {
+    let mut positions = result
+        .marks
+        .iter()
+        .map(|mark| (mark.line, mark.column, mark.mark.to_string()))
+        .collect::<Vec<_>>();
+    positions.sort();
+    positions
+}
+
+#[test]
+fn scan_reads_notebook_cells() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let notebook = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Analysis\n",
+    "- [ ] check outliers\n",
+    "- [x] load data\n",
+    "<!-- NOTE: draft -->"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": null,
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "df = load()\n",
+    "s = \"# WARN: string\"  # FIXME: slow\n"
+   ]
+  },
+  {
+   "cell_type": "raw",
+   "metadata": {},
+   "source": ["# TODO: raw"]
+  }
+ ],
+ "metadata": {
+  "kernelspec": {"language": "python", "name": "python3"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+    fs::write(temp.path().join("analysis.ipynb"), notebook)?;
+
+    let plain = ScanConfig::builder().root(temp.path()).build();
+    assert!(
+        scan(plain)?
+            .marks
+            .iter()
+            .all(|mark| mark.message != "check outliers")
+    );
+
+    let config = ScanConfig::builder()
+        .root(temp.path())
+        .task_marks(true)
+        .build();
+    let mut marks = scan(config)?
+        .marks
+        .into_iter()
+        .map(|mark| {
+            (
+                mark.line,
+                mark.mark.to_string(),
+                mark.language.to_string(),
+                mark.cell.map(|cell| (cell.index, cell.line)),
+                mark.message,
+            )
+        })
+        .collect::<Vec<_>>();
+    marks.sort();
+
+    assert_eq!(
+        marks,
+        vec![
+            (
+                8,
+                "TODO".to_string(),
+                "markdown".to_string(),
+                Some((0, 2)),
+                "check outliers".to_string()
+            ),
+            (
+                10,
+                "NOTE".to_string(),
+                "markdown".to_string(),
+                Some((0, 4)),
+                "draft".to_string()
+            ),
+            (
+                20,
+                "FIXME".to_string(),
+                "python".to_string(),
+                Some((1, 2)),
+                "slow".to_string()
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_locates_notebook_sources_after_outputs() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    let notebook = r##"{
+ "cells": [
+  {
+   "cell_type": "code",
+   "outputs": [
+    {"name": "stdout", "output_type": "stream", "text": ["x = 1  # TODO: echoed\n"]}
+   ],
+   "source": ["x = 1  # TODO: echoed\n"]
+  },
+  {
+   "cell_type": "code",
+   "outputs": [],
+   "source": ["y = \"\u00e9\"  # FIXME: escaped\n"]
+  },
+  {
+   "cell_type": "code",
+   "outputs": [],
+   "source": "a = 1\nb = 2  # NOTE: text\n"
+  }
+ ],
+ "metadata": {"kernelspec": {"language": "python"}}
+}
+"##;
+    fs::write(temp.path().join("echo.ipynb"), notebook)?;
+
+    let config = ScanConfig::builder().root(temp.path()).build();
+    let mut marks = scan(config)?
+        .marks
+        .into_iter()
+        .map(|mark| {
+            (
+                mark.line,
+                mark.column,
+                mark.mark.to_string(),
+                mark.cell.map(|cell| (cell.index, cell.line)),
+            )
+        })
+        .collect::<Vec<_>>();
+    marks.sort();
+
+    assert_eq!(
+        marks,
+        vec![
+            (8, 25, "TODO".to_string(), Some((0, 1))),
+            (13, 34, "FIXME".to_string(), Some((1, 1))),
+            (18, 31, "NOTE".to_string(), Some((2, 2))),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_decodes_notebook_surrogate_pairs() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "emoji.ipynb",
+        &[r##"{"cell_type": "code", "source": ["s = \"\ud83d\ude00\"  # TODO: emoji\n"]}"##],
+    )?;
+
+    assert_eq!(
+        positions(&scan_dir(&temp)?),
+        vec![(3, 59, "TODO".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_decodes_notebook_escaped_slashes() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "url.ipynb",
+        &[r##"{"cell_type": "code", "source": ["u = \"http:\/\/x\"  # NOTE: slash\n"]}"##],
+    )?;
+
+    assert_eq!(
+        positions(&scan_dir(&temp)?),
+        vec![(3, 57, "NOTE".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_notebook_cells_with_reordered_keys() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "order.ipynb",
+        &[
+            r##"{"source": ["x = 1  # TODO: first\n"], "outputs": [], "cell_type": "code"}"##,
+            r##"{"metadata": {}, "source": "y = 2  # FIXME: second\n", "cell_type": "code"}"##,
+        ],
+    )?;
+
+    assert_eq!(
+        positions(&scan_dir(&temp)?),
+        vec![(3, 23, "TODO".to_string()), (4, 38, "FIXME".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_ignores_source_keys_inside_notebook_outputs() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "outputs.ipynb",
+        &[
+            r##"{"cell_type": "code", "outputs": [{"data": {"source": ["# WARN: output\n"]}}], "source": ["# TODO: cell\n"]}"##,
+        ],
+    )?;
+
+    assert_eq!(
+        positions(&scan_dir(&temp)?),
+        vec![(3, 94, "TODO".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_notebooks_with_generated_first_cell() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "n.ipynb",
+        &[
+            r##"{"cell_type": "code", "source": ["# @generated\n"]}"##,
+            r##"{"cell_type": "code", "source": ["# TODO: generated\n"]}"##,
+        ],
+    )?;
+
+    let result = scan_dir(&temp)?;
+    assert!(result.marks.is_empty());
+    assert_eq!(result.stats.skips.generated, 1);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_notebooks_marked_generated_in_gitattributes() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "n.ipynb",
+        &[r##"{"cell_type": "code", "source": ["# TODO: vendored\n"]}"##],
+    )?;
+    fs::write(
+        temp.path().join(".gitattributes"),
+        "*.ipynb linguist-vendored\n",
+    )?;
+
+    let result = scan_dir(&temp)?;
+    assert!(result.marks.is_empty());
+    assert_eq!(result.stats.skips.generated, 1);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_notebooks_with_malformed_sources() -> Result<(), Box<dyn Error>> {
+    let temp = TempDir::new()?;
+    write_notebook(
+        &temp,
+        "bad.ipynb",
+        &[r##"{"cell_type": "code", "source": [1, "# TODO: after a number\n"]}"##],
+    )?;
+
+    let result = scan_dir(&temp)?;
+    assert!(result.marks.is_empty());
+    assert_eq!(result.stats.files_skipped, 1);
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use std::error::Error;
+
+mod common;
+
+use common::{lines, messages, scan_files, scan_source};
+
+#[test]
+fn scan_skips_python_string_prefixes_and_format_specs() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "fmt.py",
+        r##"a = f"{"#"} done"  # TODO: nested quotes
+b = f"{n:#x}"  # FIXME: format spec
+c = rb"\d+"  # NOTE: bytes
+def f():
+    r"""INFO: raw docstring"""
+"##,
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [
+            ("python", 1, "TODO"),
+            ("python", 2, "FIXME"),
+            ("python", 3, "NOTE"),
+            ("python", 5, "INFO"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_skips_shell_heredoc_bodies() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "gen.sh",
+        r#"cat <<EOF
+# WARN: heredoc body
+EOF
+# TODO: shell
+cat <<-'END' # FIXME: after opener
+	# WARN: indented body
+	END
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("shell", 4, "TODO"), ("shell", 5, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_shell_comments_after_arithmetic_shifts() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "gen.sh",
+        r#"mask=$((1<<bits))
+# NOTE: after arithmetic shift
+echo $(( mask << bits ))
+# INFO: after spaced shift
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("shell", 2, "NOTE"), ("shell", 4, "INFO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_ruby_heredoc_bodies() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "query.rb",
+        r#"sql = <<~SQL
+  # WARN: heredoc body
+SQL
+# TODO: ruby
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("ruby", 4, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_ruby_comments_after_shifts() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("query.rb", "flags = 1<<n\n# FIXME: after shift\n")?;
+
+    assert_eq!(lines(&marks), [("ruby", 2, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_ruby_begin_blocks() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "legacy.rb",
+        r#"=begin
+TODO: inside begin block
+=end
+x = 1 # FIXME: after block
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("ruby", 2, "TODO"), ("ruby", 4, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_perl_pod_and_skips_heredocs() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "tool.pl",
+        r#"my $x = 1; # TODO: perl
+=pod
+
+NOTE: documented in POD
+
+=cut
+print <<"END";
+# WARN: heredoc
+END
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("perl", 1, "TODO"), ("perl", 4, "NOTE")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_perl_comments_after_last_index() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("tool.pl", "my $last = $#items; # TODO: after last index\n")?;
+
+    assert_eq!(lines(&marks), [("perl", 1, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_perl_comments_after_regex_quotes() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("tool.pl", "$line =~ s/\"//g;\n# FIXME: after regex quote\n")?;
+
+    assert_eq!(lines(&marks), [("perl", 2, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_elixir_docs_and_skips_heredoc_strings() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "mix.ex",
+        r#"defmodule M do
+  @moduledoc """
+  TODO: module docs
+  """
+  x = """
+  # WARN: string
+  """
+  # FIXME: elixir
+end
+"#,
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [("elixir", 3, "TODO"), ("elixir", 8, "FIXME")]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_erlang_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("sys.erl", "%% TODO: erlang\n")?;
+
+    assert_eq!(lines(&marks), [("erlang", 1, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_r_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("stats.R", "x <- \"# WARN: string\"  # TODO: r\n")?;
+
+    assert_eq!(lines(&marks), [("r", 1, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_julia_block_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("calc.jl", "#= NOTE: julia block =#\nx = 1 # TODO: julia\n")?;
+
+    assert_eq!(lines(&marks), [("julia", 1, "NOTE"), ("julia", 2, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_powershell_block_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source("build.ps1", "<#\nFIXME: help\n#>\n# TODO: pwsh\n")?;
+
+    assert_eq!(
+        lines(&marks),
+        [("powershell", 2, "FIXME"), ("powershell", 4, "TODO")]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_reads_vim_comments_in_dotfiles() -> Result<(), Box<dyn Error>> {
+    let marks = scan_files(
+        &[(
+            ".vimrc",
+            "\" TODO: vim\nlet x = 'a \" b' \" NOTE: after\necho \"TODO: not a comment\"\ncall F(\"x\") \" FIXME: after call\n",
+        )],
+        |builder| builder.include_hidden(true),
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [("vim", 1, "TODO"), ("vim", 2, "NOTE"), ("vim", 4, "FIXME")]
+    );
+    Ok(())
+}
+
+#[test]
+fn scan_handles_lua_long_brackets() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "init.lua",
+        r#"--[[ TODO: block ]]
+local s = [[
+-- NOTE: inside a long string
+]]
+--[==[
+FIXME: level two ]] still comment
+]==]
+local t = [=[ ]] -- WARN: still string ]=] -- INFO: after
+"#,
+    )?;
+
+    assert_eq!(
+        lines(&marks),
+        [("lua", 1, "TODO"), ("lua", 6, "FIXME"), ("lua", 8, "INFO")]
+    );
+    assert_eq!(
+        messages(&marks),
+        [
+            ("lua", 1, "block"),
+            ("lua", 6, "level two ]] still comment"),
+            ("lua", 8, "after"),
+        ]
+    );
+    Ok(())
+}
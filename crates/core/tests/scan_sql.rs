@@ -0,0 +1,54 @@
+use std::error::Error;
+
+mod common;
+
+use common::{lines, scan_source};
+
+#[test]
+fn scan_skips_sql_quoted_strings() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "schema.sql",
+        "SELECT 'it''s -- NOTE: string' FROM t; -- TODO: index\n/* FIXME: block */\n",
+    )?;
+
+    assert_eq!(lines(&marks), [("sql", 1, "TODO"), ("sql", 2, "FIXME")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_dollar_quotes_in_plain_sql() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "migrate.sql",
+        "DO $$ -- WARN: body $$; -- NOTE: after body\n",
+    )?;
+
+    assert_eq!(lines(&marks), [("sql", 1, "NOTE")]);
+    Ok(())
+}
+
+#[test]
+fn scan_skips_postgresql_dollar_quotes() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "fn.pgsql",
+        r#"CREATE FUNCTION f() RETURNS text AS $body$
+  -- NOTE: function body
+$body$ LANGUAGE sql; -- TODO: volatile
+SELECT $$ /* WARN: string */ $$;
+/* outer /* FIXME: nested */ */
+"#,
+    )?;
+
+    assert_eq!(lines(&marks), [("postgresql", 3, "TODO")]);
+    Ok(())
+}
+
+#[test]
+fn scan_reads_mysql_hash_comments() -> Result<(), Box<dyn Error>> {
+    let marks = scan_source(
+        "query.mysql",
+        "DELIMITER $$\nSELECT `a#b`, '# NOTE: string' FROM t; # TODO: hash\n-- FIXME: dashes\n",
+    )?;
+
+    assert_eq!(lines(&marks), [("mysql", 2, "TODO"), ("mysql", 3, "FIXME")]);
+    Ok(())
+}